@@ -0,0 +1,223 @@
+use std::{collections::{BTreeMap, BTreeSet}, net::{Ipv4Addr, Ipv6Addr}, os::unix::fs::MetadataExt, path::Path};
+
+use chaos_core::{action::audit::{AuditListeningPortsParameters, AuditPermissionsParameters, PermissionPolicy}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use nix::unistd::{Uid, User};
+
+use crate::actions::metrics::{get_pid_of_process_by_name, get_pid_of_service, get_running_process_ids};
+
+const PROC_NET_FILES : [&str; 4] = ["tcp", "tcp6", "udp", "udp6"];
+/// TCP_LISTEN state in /proc/net/tcp
+const TCP_LISTEN : &str = "0A";
+/// TCP_CLOSE state: UDP sockets bound without a remote peer
+const UDP_UNCONNECTED : &str = "07";
+
+/// Fails with the list of files that break the permission policy
+pub fn audit_permissions(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : AuditPermissionsParameters = parameters.try_into()?;
+    log::info!("Auditing permissions of {:?}", parameters.folders);
+    let mut owners = BTreeMap::new();
+    let mut offending = Vec::new();
+    for folder in &parameters.folders {
+        audit_path(Path::new(folder), &parameters.policy, &mut owners, &mut offending);
+    }
+    if offending.is_empty() {
+        return Ok(())
+    }
+    Err(ChaosError::Other(format!("{} files break the permission policy: {}", offending.len(), offending.join("; "))))
+}
+
+fn audit_path(path : &Path, policy : &PermissionPolicy, owners : &mut BTreeMap<u32, String>, offending : &mut Vec<String>) {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    let uid = metadata.uid();
+    let owner = owners.entry(uid).or_insert_with(|| user_name(uid));
+    let location = path.to_string_lossy();
+    let reasons = policy.check(&location, metadata.mode(), metadata.file_type().is_symlink(), owner, uid);
+    if !reasons.is_empty() {
+        offending.push(format!("{} {}", location, reasons.join(", ")));
+    }
+    if !metadata.is_dir() {
+        return
+    }
+    let entries = match std::fs::read_dir(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        audit_path(&entry.path(), policy, owners, offending);
+    }
+}
+
+fn user_name(uid : u32) -> String {
+    match User::from_uid(Uid::from_raw(uid)) {
+        Ok(Some(user)) => user.name,
+        _ => uid.to_string()
+    }
+}
+
+/// Socket of /proc/net/{tcp,udp}[6] waiting for connections
+#[derive(Debug, Clone, PartialEq)]
+struct ListeningSocket {
+    protocol : &'static str,
+    address : String,
+    port : u16,
+    inode : u64
+}
+
+/// Fails with the ports opened by the application processes that are not in the allowlist
+pub fn audit_listening_ports(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : AuditListeningPortsParameters = parameters.try_into()?;
+    let main_pid = match (&parameters.process_name, &parameters.service_name) {
+        (Some(process), _) => get_pid_of_process_by_name(process)?,
+        (None, Some(service)) => get_pid_of_service(service)?,
+        (None, None) => return Err(ChaosError::Other("No application process to audit".into()))
+    };
+    // systemd reports MainPID=0 when the service is not running. The tree of 0 is every process of the host
+    if main_pid == 0 {
+        return Err(ChaosError::Other("Cannot audit listening ports: service not running".into()))
+    }
+    let pids = process_tree(main_pid)?;
+    log::info!("Auditing listening ports of processes {:?}", pids);
+    let mut socket_owners = BTreeMap::new();
+    for pid in &pids {
+        for inode in socket_inodes_of_pid(*pid) {
+            socket_owners.insert(inode, *pid);
+        }
+    }
+    let mut offending = Vec::new();
+    for protocol in PROC_NET_FILES {
+        let content = match std::fs::read_to_string(format!("/proc/net/{}", protocol)) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        for socket in parse_listening_sockets(protocol, &content) {
+            let pid = match socket_owners.get(&socket.inode) {
+                Some(v) => v,
+                None => continue
+            };
+            if parameters.allowed_ports.iter().any(|v| v.allows(socket.protocol, socket.port)) {
+                continue
+            }
+            offending.push(format!("{} {}:{} (pid {})", socket.protocol, socket.address, socket.port, pid));
+        }
+    }
+    if offending.is_empty() {
+        return Ok(())
+    }
+    Err(ChaosError::Other(format!("Ports not allowed: {}", offending.join("; "))))
+}
+
+/// The process and all its descendants
+fn process_tree(root : u32) -> ChaosResult<BTreeSet<u32>> {
+    let mut parents = BTreeMap::new();
+    for pid in get_running_process_ids()? {
+        if let Some(ppid) = parent_pid(pid) {
+            parents.insert(pid, ppid);
+        }
+    }
+    let mut tree = BTreeSet::new();
+    tree.insert(root);
+    loop {
+        let before = tree.len();
+        for (pid, ppid) in &parents {
+            if tree.contains(ppid) {
+                tree.insert(*pid);
+            }
+        }
+        if tree.len() == before {
+            return Ok(tree)
+        }
+    }
+}
+
+fn parent_pid(pid : u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The process name can contain spaces and parentheses: pid (name) state ppid
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn socket_inodes_of_pid(pid : u32) -> Vec<u64> {
+    let entries = match std::fs::read_dir(format!("/proc/{}/fd", pid)) {
+        Ok(v) => v,
+        Err(_) => return Vec::new()
+    };
+    entries.flatten()
+        .filter_map(|entry| std::fs::read_link(entry.path()).ok())
+        .filter_map(|link| {
+            let link = link.to_string_lossy().to_string();
+            link.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+        })
+        .collect()
+}
+
+fn parse_listening_sockets(protocol : &'static str, content : &str) -> Vec<ListeningSocket> {
+    let listen_state = if protocol.starts_with("tcp") { TCP_LISTEN } else { UDP_UNCONNECTED };
+    let mut sockets = Vec::new();
+    // First line is the header
+    for line in content.lines().skip(1) {
+        let columns : Vec<&str> = line.split_whitespace().collect();
+        if columns.len() < 10 || columns[3] != listen_state {
+            continue
+        }
+        let (address, port) = match columns[1].split_once(':') {
+            Some(v) => v,
+            None => continue
+        };
+        let (address, port) = match (parse_proc_address(address), u16::from_str_radix(port, 16)) {
+            (Some(address), Ok(port)) => (address, port),
+            _ => continue
+        };
+        let inode = match columns[9].parse() {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        sockets.push(ListeningSocket { protocol, address, port, inode });
+    }
+    sockets
+}
+
+/// Addresses are stored as 32 bit words in host byte order
+fn parse_proc_address(hex : &str) -> Option<String> {
+    match hex.len() {
+        8 => {
+            let word = u32::from_str_radix(hex, 16).ok()?;
+            Some(Ipv4Addr::from(word.to_le_bytes()).to_string())
+        },
+        32 => {
+            let mut bytes = [0u8; 16];
+            for i in 0..4 {
+                let word = u32::from_str_radix(&hex[i * 8..i * 8 + 8], 16).ok()?;
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+            }
+            Some(Ipv6Addr::from(bytes).to_string())
+        },
+        _ => None
+    }
+}
+
+#[test]
+fn should_parse_listening_sockets() {
+    let tcp = r#"  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21431 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:9C40 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 55123 1 0000000000000000 20 4 30 10 -1
+"#;
+    let sockets = parse_listening_sockets("tcp", tcp);
+    assert_eq!(vec![ListeningSocket { protocol : "tcp", address : "127.0.0.1".into(), port : 8080, inode : 21431 }], sockets);
+
+    let tcp6 = r#"  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 19021 1 0000000000000000 100 0 0 10 0
+"#;
+    let sockets = parse_listening_sockets("tcp6", tcp6);
+    assert_eq!("::1", sockets[0].address);
+    assert_eq!(22, sockets[0].port);
+
+    let udp = r#"   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  1044: 00000000:14E9 00000000:0000 07 00000000:00000000 00:00000000 00000000   104        0 18345 2 0000000000000000 0
+"#;
+    let sockets = parse_listening_sockets("udp", udp);
+    assert_eq!("0.0.0.0", sockets[0].address);
+    assert_eq!(5353, sockets[0].port);
+}
@@ -0,0 +1,17 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use chaos_core::{action::AuditActionType, err::ChaosResult, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+pub fn audit_action(action : &AuditActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        AuditActionType::Permissions => audit_permissions(parameters),
+        AuditActionType::ListeningPorts => audit_listening_ports(parameters),
+    }
+}
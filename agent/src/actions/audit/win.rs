@@ -0,0 +1,10 @@
+use chaos_core::{err::{ChaosError, ChaosResult}, parameters::TestParameters};
+
+/// Windows permissions are ACLs, not modes and owners
+pub fn audit_permissions(_parameters : &TestParameters) -> ChaosResult<()> {
+    Err(ChaosError::Other("Permission audit is not supported in Windows".into()))
+}
+
+pub fn audit_listening_ports(_parameters : &TestParameters) -> ChaosResult<()> {
+    Err(ChaosError::Other("Listening ports audit is not supported in Windows".into()))
+}
@@ -59,7 +59,7 @@ fn gen_mark(params : &DnsParameters) -> String {
     format!("MARK-{}-{}", params.ip, params.domain)
 }
 
-fn read_etc_file() -> ChaosResult<Vec<String>> {
+pub(crate) fn read_etc_file() -> ChaosResult<Vec<String>> {
     let content = std::fs::read_to_string(ETC_HOST_LOCATION).map_err(|e| ChaosError::Other(format!("Cannot read /etc/hosts: {e}")))?;
     Ok(content.lines().map(|v| v.into()).collect())
 }
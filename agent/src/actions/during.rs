@@ -0,0 +1,111 @@
+use std::{cell::RefCell, collections::BTreeMap, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle, time::{Duration, Instant}};
+
+use chaos_core::{action::{names::DURING_INJECTIONS, TestActionType}, err::ChaosResult, parameters::{TestParameter, TestParameters}};
+
+use crate::{common::{now_milliseconds, AgentTaskInternal}, state::AgentState};
+
+use super::{resolve_action, run_stateless_action};
+
+/// Action of a during block already resolved, with its interval
+struct ScheduledAction {
+    name : String,
+    action : TestActionType,
+    parameters : TestParameters,
+    every : Duration
+}
+
+/// Task id, stopper and the thread that returns the injections
+type RunningSchedule = (u32, Arc<AtomicBool>, JoinHandle<Vec<TestParameter>>);
+
+thread_local! {
+    static SCHEDULE : RefCell<Option<RunningSchedule>> = const { RefCell::new(None) };
+}
+
+/// Starts the background actions of the task if they are not running yet
+pub fn start_during_actions(state : &AgentState, task : &AgentTaskInternal) -> ChaosResult<()> {
+    if task.during.is_empty() || SCHEDULE.with_borrow(|v| v.as_ref().map(|(id, _, _)| *id == task.id).unwrap_or(false)) {
+        return Ok(())
+    }
+    // Leftovers of a task cleaned by the server
+    stop_schedule();
+    let mut actions = Vec::with_capacity(task.during.len());
+    for during in &task.during {
+        let (action, parameters) = resolve_action(&during.action, state, task)?;
+        actions.push(ScheduledAction {
+            name : <&str>::from(&during.action).to_string(),
+            action,
+            parameters,
+            every : during.every.max(Duration::from_millis(100))
+        });
+    }
+    log::info!("Starting {} background actions for task {}", actions.len(), task.id);
+    let stopper = Arc::new(AtomicBool::new(true));
+    let thread_stopper = stopper.clone();
+    let handle = std::thread::spawn(move || run_schedule(actions, thread_stopper));
+    SCHEDULE.with_borrow_mut(|v| *v = Some((task.id, stopper, handle)));
+    Ok(())
+}
+
+/// Stops the background actions of the finished task and records the injections in its data
+pub fn stop_during_actions(task : &mut AgentTaskInternal) {
+    if task.during.is_empty() {
+        return
+    }
+    if let Some(injections) = stop_schedule() {
+        task.data.insert(DURING_INJECTIONS, TestParameter::Vec(injections));
+    }
+}
+
+fn stop_schedule() -> Option<Vec<TestParameter>> {
+    let (id, stopper, handle) = SCHEDULE.with_borrow_mut(|v| v.take())?;
+    log::info!("Stopping background actions of task {}", id);
+    stopper.store(false, Ordering::Relaxed);
+    handle.join().ok()
+}
+
+fn run_schedule(actions : Vec<ScheduledAction>, stopper : Arc<AtomicBool>) -> Vec<TestParameter> {
+    let start = Instant::now();
+    let mut next : Vec<Duration> = actions.iter().map(|v| v.every).collect();
+    let mut injections = Vec::new();
+    let mut undo : Vec<(TestActionType, &TestParameters)> = Vec::new();
+    while stopper.load(Ordering::Relaxed) {
+        let elapsed = start.elapsed();
+        for (scheduled, next) in actions.iter().zip(next.iter_mut()) {
+            if elapsed < *next {
+                continue
+            }
+            *next += scheduled.every;
+            log::info!("Injecting {} in background", scheduled.name);
+            let timestamp = now_milliseconds();
+            let res = run_stateless_action(&scheduled.action, &scheduled.parameters);
+            if let Err(e) = &res {
+                log::warn!("Error injecting {} in background: {}", scheduled.name, e);
+            }
+            injections.push(injection(&scheduled.name, timestamp, res));
+            if let Some(action) = scheduled.action.undo_action() {
+                if !undo.iter().any(|(v, _)| v == &action) {
+                    undo.push((action, &scheduled.parameters));
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    // The faults are kept by this thread, so they cannot outlive the phase
+    for (action, parameters) in undo.into_iter().rev() {
+        if let Err(e) = run_stateless_action(&action, parameters) {
+            log::warn!("Error undoing {} after the background actions: {}", <&str>::from(&action), e);
+        }
+    }
+    injections
+}
+
+fn injection(name : &str, timestamp : i64, res : ChaosResult<()>) -> TestParameter {
+    let mut injection = BTreeMap::new();
+    injection.insert("action".to_string(), TestParameter::Text(name.to_string()));
+    injection.insert("timestamp".to_string(), TestParameter::I64(timestamp));
+    injection.insert("result".to_string(), TestParameter::Text(match res {
+        Ok(_) => "Ok".to_string(),
+        Err(e) => e.to_string()
+    }));
+    TestParameter::Obj(injection)
+}
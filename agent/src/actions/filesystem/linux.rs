@@ -0,0 +1,59 @@
+use std::{os::{fd::AsRawFd, unix::fs::MetadataExt}, path::Path};
+
+use chaos_core::err::{ChaosError, ChaosResult};
+use nix::{fcntl::{fallocate, FallocateFlags}, mount::{mount, umount, MsFlags}, sys::statvfs::statvfs};
+
+/// Free bytes in the volume of the path. Without the reserved blocks it is the space available to unprivileged users
+pub fn free_space(path : &str, reserved : bool) -> ChaosResult<u64> {
+    let stat = statvfs(path).map_err(|e| ChaosError::Other(format!("Cannot get free space of {}: {}", path, e)))?;
+    let blocks = if reserved { stat.blocks_free() } else { stat.blocks_available() };
+    Ok(blocks as u64 * stat.fragment_size() as u64)
+}
+
+/// Reserves the blocks of the file. A sparse file would not use the free space
+pub fn allocate_file(path : &Path, size : u64) -> ChaosResult<()> {
+    let file = std::fs::File::create(path).map_err(|e| ChaosError::Other(format!("Cannot create filler file {}: {}", path.to_string_lossy(), e)))?;
+    fallocate(file.as_raw_fd(), FallocateFlags::empty(), 0, size as i64).map_err(|e| ChaosError::Other(format!("Cannot allocate {} bytes for filler file {}: {}", size, path.to_string_lossy(), e)))
+}
+
+pub fn same_volume(first : &Path, second : &Path) -> bool {
+    match (std::fs::metadata(first), std::fs::metadata(second)) {
+        (Ok(first), Ok(second)) => first.dev() == second.dev(),
+        _ => false
+    }
+}
+
+/// Bind mount of the folder over itself, remounted as read-only
+pub fn mount_read_only(path : &str) -> ChaosResult<()> {
+    mount(Some(path), path, None::<&str>, MsFlags::MS_BIND, None::<&str>).map_err(|e| ChaosError::Other(format!("Cannot bind mount {}: {}", path, e)))?;
+    if let Err(e) = mount(None::<&str>, path, None::<&str>, MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY, None::<&str>) {
+        let _ = umount(path);
+        return Err(ChaosError::Other(format!("Cannot remount {} as read-only: {}", path, e)))
+    }
+    Ok(())
+}
+
+/// The content of the folder is hidden until the tmpfs is unmounted
+pub fn mount_tmpfs(path : &str, size : &str) -> ChaosResult<()> {
+    mount(Some("tmpfs"), path, Some("tmpfs"), MsFlags::empty(), Some(format!("size={}", size).as_str())).map_err(|e| ChaosError::Other(format!("Cannot mount tmpfs over {}: {}", path, e)))
+}
+
+pub fn unmount(path : &str) -> ChaosResult<()> {
+    umount(path).map_err(|e| ChaosError::Other(format!("Cannot unmount {}: {}", path, e)))
+}
+
+#[test]
+fn should_allocate_filler_file() {
+    let folder = std::env::temp_dir();
+    let path = folder.join(format!("chaos-filler-{}", std::process::id()));
+    allocate_file(&path, 1024 * 1024).unwrap();
+    let metadata = std::fs::metadata(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(1024 * 1024, metadata.len());
+    // Not sparse
+    assert!(metadata.blocks() * 512 >= 1024 * 1024);
+    assert!(same_volume(&folder, &folder));
+    let available = free_space(&folder.to_string_lossy(), false).unwrap();
+    assert!(available > 0);
+    assert!(free_space(&folder.to_string_lossy(), true).unwrap() >= available);
+}
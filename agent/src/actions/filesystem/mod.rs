@@ -0,0 +1,120 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use std::path::{Path, PathBuf};
+
+use chaos_core::{action::{filesystem::FilesystemParameters, FilesystemActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+use crate::common::{create_file_in_temp, create_file_path_in_workspace};
+
+/// Workspace file with the faults to release. Survives agent restarts
+const FILESYSTEM_FAULTS_FILE : &str = "filesystem-faults.json";
+
+/// Filler files and mount points created by the agent
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct FilesystemFaults {
+    fillers : Vec<String>,
+    mounts : Vec<String>
+}
+
+pub fn filesystem_action(action : &FilesystemActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        FilesystemActionType::Fill => fill_filesystem(parameters),
+        FilesystemActionType::ReadOnly => mount_fault(parameters, false),
+        FilesystemActionType::Tmpfs => mount_fault(parameters, true),
+        FilesystemActionType::Release => release_filesystem(),
+    }
+}
+
+/// Creates a filler file in the volume of the path leaving only the configured bytes free
+pub fn fill_filesystem(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : FilesystemParameters = parameters.try_into()?;
+    let free = free_space(&parameters.path, parameters.fill_reserved)?;
+    if free <= parameters.free_bytes {
+        return Ok(())
+    }
+    let mut faults = load_faults();
+    let filler = filler_path(&parameters.path, faults.fillers.len())?;
+    log::info!("Filling volume of {} with {} leaving {} bytes free", parameters.path, filler.to_string_lossy(), parameters.free_bytes);
+    // Saved before allocating so a partial filler is released too
+    faults.fillers.push(filler.to_string_lossy().to_string());
+    save_faults(&faults)?;
+    allocate_file(&filler, free - parameters.free_bytes)
+}
+
+fn mount_fault(parameters : &TestParameters, tmpfs : bool) -> ChaosResult<()> {
+    let parameters : FilesystemParameters = parameters.try_into()?;
+    let mut faults = load_faults();
+    if faults.mounts.contains(&parameters.path) {
+        return Ok(())
+    }
+    if tmpfs {
+        log::info!("Mounting tmpfs of {} over {}", parameters.tmpfs_size, parameters.path);
+        mount_tmpfs(&parameters.path, &parameters.tmpfs_size)?;
+    } else {
+        log::info!("Mounting {} as read-only", parameters.path);
+        mount_read_only(&parameters.path)?;
+    }
+    faults.mounts.push(parameters.path);
+    save_faults(&faults)
+}
+
+/// Removes the filler files and unmounts the folders, the last mount first. Nothing to do if there are no faults
+pub fn release_filesystem() -> ChaosResult<()> {
+    let mut faults = load_faults();
+    while let Some(filler) = faults.fillers.pop() {
+        log::info!("Removing filler file {}", filler);
+        if let Err(e) = std::fs::remove_file(&filler) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                faults.fillers.push(filler.clone());
+                save_faults(&faults)?;
+                return Err(ChaosError::Other(format!("Cannot remove filler file {}: {}", filler, e)))
+            }
+        }
+    }
+    while let Some(mount) = faults.mounts.pop() {
+        log::info!("Unmounting {}", mount);
+        if let Err(e) = unmount(&mount) {
+            faults.mounts.push(mount);
+            save_faults(&faults)?;
+            return Err(e)
+        }
+    }
+    save_faults(&faults)
+}
+
+/// The temp workspace if it is in the same volume as the path, the path otherwise
+fn filler_path(path : &str, id : usize) -> ChaosResult<PathBuf> {
+    let name = format!("filesystem-filler-{}.bin", id);
+    let temp_file = create_file_in_temp(&name);
+    if let Some(temp) = temp_file.parent() {
+        let _ = std::fs::create_dir_all(temp);
+        if same_volume(temp, Path::new(path)) {
+            return Ok(temp_file)
+        }
+    }
+    let folder = Path::new(path);
+    if !folder.is_dir() {
+        return Err(ChaosError::Other(format!("Folder {} not found", path)))
+    }
+    Ok(folder.join(format!(".{}", name)))
+}
+
+fn load_faults() -> FilesystemFaults {
+    std::fs::read(create_file_path_in_workspace(FILESYSTEM_FAULTS_FILE))
+        .ok()
+        .and_then(|v| serde_json::from_slice(&v).ok())
+        .unwrap_or_default()
+}
+
+fn save_faults(faults : &FilesystemFaults) -> ChaosResult<()> {
+    let content = serde_json::to_vec(faults).map_err(|e| ChaosError::Other(format!("Cannot serialize filesystem faults: {}", e)))?;
+    std::fs::write(create_file_path_in_workspace(FILESYSTEM_FAULTS_FILE), content).map_err(|e| ChaosError::Other(format!("Cannot save filesystem faults: {}", e)))
+}
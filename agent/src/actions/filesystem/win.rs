@@ -0,0 +1,40 @@
+use std::path::{Component, Path};
+
+use chaos_core::err::{ChaosError, ChaosResult};
+use windows::{core::PCWSTR, Win32::Storage::FileSystem::GetDiskFreeSpaceExW};
+
+/// Free bytes in the volume of the path. Without the reserved space it is the space available to the user of the agent (quotas)
+pub fn free_space(path : &str, reserved : bool) -> ChaosResult<u64> {
+    let name : Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    let mut total_free = 0u64;
+    unsafe { GetDiskFreeSpaceExW(PCWSTR(name.as_ptr()), Some(&mut available), None, Some(&mut total_free)) }.map_err(|e| ChaosError::Other(format!("Cannot get free space of {}: {}", path, e)))?;
+    Ok(if reserved { total_free } else { available })
+}
+
+/// NTFS allocates the clusters when the length is set
+pub fn allocate_file(path : &Path, size : u64) -> ChaosResult<()> {
+    let file = std::fs::File::create(path).map_err(|e| ChaosError::Other(format!("Cannot create filler file {}: {}", path.to_string_lossy(), e)))?;
+    file.set_len(size).map_err(|e| ChaosError::Other(format!("Cannot allocate {} bytes for filler file {}: {}", size, path.to_string_lossy(), e)))
+}
+
+/// Same drive letter
+pub fn same_volume(first : &Path, second : &Path) -> bool {
+    let prefix = |path : &Path| match path.components().next() {
+        Some(Component::Prefix(v)) => Some(v.as_os_str().to_ascii_uppercase()),
+        _ => None
+    };
+    prefix(first).is_some() && prefix(first) == prefix(second)
+}
+
+pub fn mount_read_only(_path : &str) -> ChaosResult<()> {
+    Err(ChaosError::Other("Read-only mounts are not supported in Windows".into()))
+}
+
+pub fn mount_tmpfs(_path : &str, _size : &str) -> ChaosResult<()> {
+    Err(ChaosError::Other("Tmpfs mounts are not supported in Windows".into()))
+}
+
+pub fn unmount(_path : &str) -> ChaosResult<()> {
+    Err(ChaosError::Other("Mounts are not supported in Windows".into()))
+}
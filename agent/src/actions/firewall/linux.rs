@@ -0,0 +1,214 @@
+use std::{process::{Command, Stdio}, time::Duration};
+
+use chaos_core::{action::firewall::{FirewallParameters, FIREWALL_MARK_PREFIX}, err::{ChaosError, ChaosResult}};
+
+use crate::common::{spawn_child_and_check_return_code, spawn_child_and_return_stdout};
+
+/// Table owned by the agent so the rules of the system are never touched
+const NFT_TABLE : &str = "chaosbench";
+const NFT_CHAIN : &str = "output";
+
+/// Adds the rule if it does not exist. nftables is used when available, iptables otherwise
+pub fn add_firewall_rule(parameters : &FirewallParameters, mark : &str) -> ChaosResult<()> {
+    if has_nft() {
+        add_nft_rule(parameters, mark)
+    } else {
+        add_iptables_rule(parameters, mark)
+    }
+}
+
+/// Removes the rules whose comment is the mark or, with the mark prefix, all the tagged rules. Nothing to do if there are no rules
+pub fn remove_firewall_rules(mark : &str) -> ChaosResult<()> {
+    if has_nft() {
+        remove_nft_rules(mark)
+    } else {
+        remove_iptables_rules("iptables", mark)?;
+        remove_iptables_rules("ip6tables", mark)
+    }
+}
+
+fn has_nft() -> bool {
+    Command::new("nft").arg("--version").stdout(Stdio::null()).stderr(Stdio::null()).status().map(|v| v.success()).unwrap_or(false)
+}
+
+fn add_nft_rule(parameters : &FirewallParameters, mark : &str) -> ChaosResult<()> {
+    run("nft", &["add", "table", "inet", NFT_TABLE], "Cannot create nftables table")?;
+    run("nft", &["add", "chain", "inet", NFT_TABLE, NFT_CHAIN, "{ type filter hook output priority 0 ; policy accept ; }"], "Cannot create nftables chain")?;
+    if !nft_rule_handles(&list_nft_rules()?, mark).is_empty() {
+        return Ok(())
+    }
+    let mut args = vec!["add".to_string(), "rule".to_string(), "inet".to_string(), NFT_TABLE.to_string(), NFT_CHAIN.to_string()];
+    args.extend(nft_rule(parameters, mark));
+    let args : Vec<&str> = args.iter().map(|v| v.as_str()).collect();
+    run("nft", &args, "Cannot add nftables rule")
+}
+
+fn remove_nft_rules(mark : &str) -> ChaosResult<()> {
+    let rules = match list_nft_rules() {
+        Ok(v) => v,
+        // The table does not exist
+        Err(_) => return Ok(())
+    };
+    for handle in nft_rule_handles(&rules, mark) {
+        run("nft", &["delete", "rule", "inet", NFT_TABLE, NFT_CHAIN, "handle", &handle.to_string()], "Cannot delete nftables rule")?;
+    }
+    Ok(())
+}
+
+fn list_nft_rules() -> ChaosResult<String> {
+    let mut cmd = Command::new("nft");
+    cmd.arg("-a").arg("list").arg("chain").arg("inet").arg(NFT_TABLE).arg(NFT_CHAIN).stdout(Stdio::piped()).stderr(Stdio::null());
+    spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "Cannot list nftables rules")
+}
+
+/// Ex: ip daddr 10.0.0.5 tcp dport 5432 drop comment "ChaosBench-10.0.0.5-5432-tcp"
+fn nft_rule(parameters : &FirewallParameters, mark : &str) -> Vec<String> {
+    let mut rule = Vec::with_capacity(12);
+    if let Some(host) = &parameters.host {
+        rule.push(if parameters.is_ipv6() { "ip6" } else { "ip" }.to_string());
+        rule.push("daddr".to_string());
+        rule.push(host.clone());
+    }
+    if let Some(port) = parameters.port {
+        rule.push(parameters.protocol.clone());
+        rule.push("dport".to_string());
+        rule.push(port.to_string());
+    }
+    if !parameters.reject {
+        rule.push("drop".to_string());
+    } else if parameters.port.is_some() && parameters.protocol == "tcp" {
+        rule.push("reject with tcp reset".to_string());
+    } else {
+        rule.push("reject".to_string());
+    }
+    rule.push("comment".to_string());
+    rule.push(format!("\"{}\"", mark));
+    rule
+}
+
+/// Handles of the rules tagged with the mark. A prefix mark matches all the tagged rules
+fn nft_rule_handles(rules : &str, mark : &str) -> Vec<u64> {
+    let needle = nft_comment_needle(mark);
+    rules.lines()
+        .filter(|line| line.contains(&needle))
+        .filter_map(|line| line.rsplit_once("# handle ").and_then(|(_, handle)| handle.trim().parse().ok()))
+        .collect()
+}
+
+fn nft_comment_needle(mark : &str) -> String {
+    if mark == FIREWALL_MARK_PREFIX {
+        format!("comment \"{}", mark)
+    } else {
+        format!("comment \"{}\"", mark)
+    }
+}
+
+fn add_iptables_rule(parameters : &FirewallParameters, mark : &str) -> ChaosResult<()> {
+    let program = if parameters.is_ipv6() { "ip6tables" } else { "iptables" };
+    let rule = iptables_rule(parameters, mark);
+    let mut check = vec!["-C", "OUTPUT"];
+    check.extend(rule.iter().map(|v| v.as_str()));
+    if run(program, &check, "Rule not found").is_ok() {
+        return Ok(())
+    }
+    let mut add = vec!["-A", "OUTPUT"];
+    add.extend(rule.iter().map(|v| v.as_str()));
+    run(program, &add, "Cannot add iptables rule")
+}
+
+fn remove_iptables_rules(program : &str, mark : &str) -> ChaosResult<()> {
+    let mut cmd = Command::new(program);
+    cmd.arg("-S").arg("OUTPUT").stdout(Stdio::piped()).stderr(Stdio::null());
+    let rules = match spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "Cannot list iptables rules") {
+        Ok(v) => v,
+        // ip6tables not installed or IPv6 disabled
+        Err(_) => return Ok(())
+    };
+    for rule in iptables_tagged_rules(&rules, mark) {
+        let mut args = vec!["-D"];
+        args.extend(rule.iter().map(|v| v.as_str()));
+        run(program, &args, "Cannot delete iptables rule")?;
+    }
+    Ok(())
+}
+
+/// Ex: -d 10.0.0.5 -p tcp --dport 5432 -m comment --comment ChaosBench-10.0.0.5-5432-tcp -j DROP
+fn iptables_rule(parameters : &FirewallParameters, mark : &str) -> Vec<String> {
+    let mut rule = Vec::with_capacity(14);
+    if let Some(host) = &parameters.host {
+        rule.push("-d".to_string());
+        rule.push(host.clone());
+    }
+    if let Some(port) = parameters.port {
+        rule.push("-p".to_string());
+        rule.push(parameters.protocol.clone());
+        rule.push("--dport".to_string());
+        rule.push(port.to_string());
+    }
+    rule.extend(["-m", "comment", "--comment", mark].iter().map(|v| v.to_string()));
+    rule.push("-j".to_string());
+    if !parameters.reject {
+        rule.push("DROP".to_string());
+    } else {
+        rule.push("REJECT".to_string());
+        if parameters.port.is_some() && parameters.protocol == "tcp" {
+            rule.push("--reject-with".to_string());
+            rule.push("tcp-reset".to_string());
+        }
+    }
+    rule
+}
+
+/// Chain and rule specification of the tagged rules in the output of iptables -S
+fn iptables_tagged_rules(rules : &str, mark : &str) -> Vec<Vec<String>> {
+    rules.lines()
+        .filter_map(|line| line.strip_prefix("-A "))
+        .filter(|rule| {
+            let mut columns = rule.split_whitespace();
+            columns.any(|v| v == "--comment") && columns.next().map(|v| {
+                let v = v.trim_matches('"');
+                if mark == FIREWALL_MARK_PREFIX { v.starts_with(mark) } else { v == mark }
+            }).unwrap_or(false)
+        })
+        .map(|rule| rule.split_whitespace().map(|v| v.to_string()).collect())
+        .collect()
+}
+
+fn run(program : &str, args : &[&str], err_msg : &str) -> ChaosResult<()> {
+    let mut cmd = Command::new(program);
+    cmd.args(args).stdout(Stdio::null()).stderr(Stdio::null());
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(10), err_msg).map_err(|e| match e {
+        ChaosError::Other(msg) => ChaosError::Other(format!("{} ({} {})", msg, program, args.join(" "))),
+        e => e
+    })
+}
+
+#[test]
+fn should_build_and_find_tagged_rules() {
+    let parameters = FirewallParameters {
+        host : Some("10.0.0.5".into()),
+        port : Some(5432),
+        protocol : "tcp".into(),
+        reject : true
+    };
+    let mark = parameters.mark();
+    assert_eq!("ip daddr 10.0.0.5 tcp dport 5432 reject with tcp reset comment \"ChaosBench-10.0.0.5-5432-tcp\"", nft_rule(&parameters, &mark).join(" "));
+    assert_eq!("-d 10.0.0.5 -p tcp --dport 5432 -m comment --comment ChaosBench-10.0.0.5-5432-tcp -j REJECT --reject-with tcp-reset", iptables_rule(&parameters, &mark).join(" "));
+
+    let nft = r#"table inet chaosbench {
+	chain output { # handle 1
+		type filter hook output priority filter; policy accept;
+		ip daddr 10.0.0.5 tcp dport 5432 reject with tcp reset comment "ChaosBench-10.0.0.5-5432-tcp" # handle 2
+		udp dport 53 drop comment "ChaosBench-any-53-udp" # handle 3
+		tcp dport 22 drop comment "Admin" # handle 4
+	}
+}"#;
+    assert_eq!(vec![2], nft_rule_handles(nft, &mark));
+    assert_eq!(vec![2, 3], nft_rule_handles(nft, FIREWALL_MARK_PREFIX));
+
+    let iptables = "-P OUTPUT ACCEPT\n-A OUTPUT -d 10.0.0.5/32 -p tcp -m tcp --dport 5432 -m comment --comment ChaosBench-10.0.0.5-5432-tcp -j REJECT --reject-with tcp-reset\n-A OUTPUT -p udp -m comment --comment \"Other rule\" -j DROP\n";
+    let rules = iptables_tagged_rules(iptables, &mark);
+    assert_eq!(1, rules.len());
+    assert_eq!("OUTPUT", rules[0][0]);
+    assert!(iptables_tagged_rules(iptables, "ChaosBench-10.0.0.6-any-tcp").is_empty());
+}
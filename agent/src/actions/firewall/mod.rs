@@ -0,0 +1,34 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use chaos_core::{action::{firewall::{FirewallParameters, FIREWALL_MARK_PREFIX}, FirewallActionType}, err::ChaosResult, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+pub fn firewall_action(action : &FirewallActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        FirewallActionType::Block => block_traffic(parameters),
+        FirewallActionType::Unblock => unblock_traffic(parameters),
+    }
+}
+
+pub fn block_traffic(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : FirewallParameters = parameters.try_into()?;
+    let mark = parameters.mark();
+    log::info!("Adding firewall rule {}", mark);
+    add_firewall_rule(&parameters, &mark)
+}
+
+/// Removes the rules of the firewall parameters or, without them, every rule created by the agent
+pub fn unblock_traffic(parameters : &TestParameters) -> ChaosResult<()> {
+    let mark = match FirewallParameters::try_from(parameters) {
+        Ok(v) => v.mark(),
+        Err(_) => FIREWALL_MARK_PREFIX.to_string()
+    };
+    log::info!("Removing firewall rules {}", mark);
+    remove_firewall_rules(&mark)
+}
@@ -0,0 +1,48 @@
+use std::{process::{Command, Stdio}, time::Duration};
+
+use chaos_core::{action::firewall::{FirewallParameters, FIREWALL_MARK_PREFIX}, err::ChaosResult};
+
+use crate::common::{spawn_child_and_check_return_code, spawn_child_and_return_stdout};
+
+/// The mark is the name of the Windows Firewall rule. Windows Firewall cannot reject, blocked traffic is always dropped
+pub fn add_firewall_rule(parameters : &FirewallParameters, mark : &str) -> ChaosResult<()> {
+    if !firewall_rule_names()?.iter().any(|v| v == mark) {
+        let mut cmd = Command::new("netsh");
+        cmd.arg("advfirewall").arg("firewall").arg("add").arg("rule")
+            .arg(format!("name={}", mark))
+            .arg("dir=out")
+            .arg("action=block");
+        if let Some(host) = &parameters.host {
+            cmd.arg(format!("remoteip={}", host));
+        }
+        if let Some(port) = parameters.port {
+            cmd.arg(format!("protocol={}", parameters.protocol.to_uppercase())).arg(format!("remoteport={}", port));
+        }
+        spawn_child_and_check_return_code(cmd, Duration::from_secs(10), "Cannot add firewall rule using netsh")?;
+    }
+    Ok(())
+}
+
+pub fn remove_firewall_rules(mark : &str) -> ChaosResult<()> {
+    for name in firewall_rule_names()? {
+        let tagged = if mark == FIREWALL_MARK_PREFIX { name.starts_with(mark) } else { name == mark };
+        if !tagged {
+            continue
+        }
+        let mut cmd = Command::new("netsh");
+        cmd.arg("advfirewall").arg("firewall").arg("delete").arg("rule").arg(format!("name={}", name));
+        spawn_child_and_check_return_code(cmd, Duration::from_secs(10), "Cannot delete firewall rule using netsh")?;
+    }
+    Ok(())
+}
+
+/// Names of the rules created by the agent
+fn firewall_rule_names() -> ChaosResult<Vec<String>> {
+    let mut cmd = Command::new("netsh");
+    cmd.arg("advfirewall").arg("firewall").arg("show").arg("rule").arg("name=all").arg("dir=out").stdout(Stdio::piped());
+    let stdout = spawn_child_and_return_stdout(cmd, Duration::from_secs(30), "Cannot list firewall rules using netsh")?;
+    Ok(stdout.lines()
+        .filter_map(|line| line.split_once(':').map(|(_, name)| name.trim().to_string()))
+        .filter(|name| name.starts_with(FIREWALL_MARK_PREFIX))
+        .collect())
+}
@@ -1,93 +1,338 @@
-use chaos_core::{action::install::{InstallCheckParameters, InstallParameters, InstallWithErrorParameters}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use chaos_core::{action::{inspect::{parse_control_file, parse_package_contents, PackageInspection, MAINTAINER_SCRIPTS}, install::{get_installer_field, InstallCheckParameters, InstallInterruptedParameters, InstallParameters, InstallParametersMode, InstallWithErrorParameters}}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
 
-use crate::api::download_file;
+use std::{collections::BTreeMap, io::{Read, Write}, os::unix::process::CommandExt, path::Path, process::{Command, Stdio}, time::{Duration, Instant}};
 
+use nix::{sys::signal::killpg, unistd::Pid};
 
-pub fn execute_install(parameters: &TestParameters) -> ChaosResult<()> {
-    log::info!("Executing install");
-    let parameters: InstallParameters = parameters.try_into()?;
-    let mut command = std::process::Command::new(r"dpkg");
-    let file_location = download_file(&parameters.installer)?;
-    command
-        .arg("-i")
-        .arg(&file_location.to_string_lossy()[..]);
-    for (param, value) in &parameters.parameters {
-        command.arg(format!("{}={}", param, value));
+use crate::{actions::process::parse_signal, api::{download_file, upload_data}, common::create_file_in_temp};
+
+
+/// Package formats supported by the Linux installer actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PackageFormat {
+    Deb,
+    Rpm
+}
+
+impl PackageFormat {
+    /// Detects the format from the extension, the magic bytes of the file or the package manager of the host
+    fn detect(installer: &str, file_location: &Path) -> PackageFormat {
+        if installer.ends_with(".deb") {
+            return PackageFormat::Deb
+        }
+        if installer.ends_with(".rpm") {
+            return PackageFormat::Rpm
+        }
+        let mut header = [0u8; 8];
+        if let Ok(mut file) = std::fs::File::open(file_location) {
+            if file.read_exact(&mut header).is_ok() {
+                if let Some(format) = PackageFormat::from_magic(&header) {
+                    return format
+                }
+            }
+        }
+        PackageFormat::from_host()
+    }
+
+    fn from_magic(header: &[u8]) -> Option<PackageFormat> {
+        if header.starts_with(&[0xED, 0xAB, 0xEE, 0xDB]) {
+            return Some(PackageFormat::Rpm)
+        }
+        if header.starts_with(b"!<arch>\n") {
+            return Some(PackageFormat::Deb)
+        }
+        None
+    }
+
+    fn from_host() -> PackageFormat {
+        if Path::new("/usr/bin/dpkg").exists() {
+            return PackageFormat::Deb
+        }
+        if Path::new("/usr/bin/rpm").exists() {
+            return PackageFormat::Rpm
+        }
+        PackageFormat::Deb
+    }
+
+    fn install_command(&self, file_location: &Path) -> Command {
+        let mut command = match self {
+            PackageFormat::Deb => {
+                let mut command = Command::new("dpkg");
+                command.arg("-i");
+                command
+            },
+            PackageFormat::Rpm => {
+                let mut command = Command::new("rpm");
+                command.arg("-i");
+                command
+            }
+        };
+        command.arg(file_location);
+        command
+    }
+
+    fn upgrade_command(&self, file_location: &Path) -> Command {
+        match self {
+            PackageFormat::Deb => self.install_command(file_location),
+            PackageFormat::Rpm => {
+                let mut command = Command::new("rpm");
+                command.arg("-U").arg(file_location);
+                command
+            }
+        }
+    }
+
+    fn uninstall_command(&self, package_name: &str) -> Command {
+        let mut command = match self {
+            PackageFormat::Deb => {
+                let mut command = Command::new("dpkg");
+                command.arg("-r");
+                command
+            },
+            PackageFormat::Rpm => {
+                let mut command = Command::new("rpm");
+                command.arg("-e");
+                command
+            }
+        };
+        command.arg(package_name);
+        command
+    }
+
+    /// Name of the package contained in the installer file
+    fn package_name(&self, file_location: &Path) -> ChaosResult<String> {
+        let output = match self {
+            PackageFormat::Deb => Command::new("dpkg-deb").arg("-f").arg(file_location).arg("Package").output(),
+            PackageFormat::Rpm => Command::new("rpm").arg("-qp").arg("--queryformat").arg("%{NAME}").arg(file_location).output(),
+        };
+        let output = output.map_err(|e| ChaosError::Other(format!("Cannot read package name of {}: {}", file_location.to_string_lossy(), e)))?;
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || name.is_empty() {
+            return Err(ChaosError::Other(format!("Cannot read package name of {}", file_location.to_string_lossy())))
+        }
+        Ok(name)
+    }
+}
+
+/// Passes the install parameters to the package: as a debconf preseed or as environment variables of the package manager.
+/// RPM packages always use environment variables.
+fn apply_install_parameters(command: &mut Command, format: PackageFormat, mode: InstallParametersMode, parameters: &BTreeMap<String, String>, file_location: &Path) -> ChaosResult<()> {
+    if parameters.is_empty() {
+        return Ok(())
+    }
+    if format == PackageFormat::Rpm || mode == InstallParametersMode::Env {
+        command.envs(parameters);
+        return Ok(())
+    }
+    let package_name = format.package_name(file_location)?;
+    let preseed = debconf_preseed(&package_name, parameters);
+    let mut child = Command::new("debconf-set-selections")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ChaosError::Other(format!("Cannot execute debconf-set-selections: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(preseed.as_bytes()).map_err(|e| ChaosError::Other(format!("Cannot write debconf preseed: {}", e)))?;
+    }
+    let output = child.wait_with_output().map_err(|e| ChaosError::Other(format!("Cannot execute debconf-set-selections: {}", e)))?;
+    if !output.status.success() {
+        return Err(ChaosError::Other(format!("Cannot load debconf preseed: {}", String::from_utf8_lossy(&output.stderr))))
     }
+    command.env("DEBIAN_FRONTEND", "noninteractive");
+    Ok(())
+}
+
+const DEBCONF_TYPES: [&str; 9] = ["string", "boolean", "select", "multiselect", "password", "note", "text", "title", "error"];
+
+/// One debconf selection per parameter. Parameters without owner are questions of the package: SERVER => package/SERVER.
+/// The question type goes after a colon (shared/accepted-license:boolean) and is string if not present
+fn debconf_preseed(package_name: &str, parameters: &BTreeMap<String, String>) -> String {
+    let mut preseed = String::with_capacity(1024);
+    for (param, value) in parameters {
+        let (param, question_type) = match param.rsplit_once(':') {
+            Some((param, question_type)) if DEBCONF_TYPES.contains(&question_type) => (param, question_type),
+            _ => (param.as_str(), "string"),
+        };
+        let question = if param.contains('/') {
+            param.to_string()
+        } else {
+            format!("{}/{}", package_name, param)
+        };
+        preseed.push_str(&format!("{} {} {} {}\n", package_name, question, question_type, value));
+    }
+    preseed
+}
+
+/// Runs the package manager and maps a failure into an error with the exit status
+fn run_package_command(mut command: Command, action: &str, installer: &str) -> ChaosResult<()> {
     let output = match command.output() {
         Ok(v) => v,
         Err(_) => {
             return Err(ChaosError::Other(format!(
-                "Cannot install {}", &parameters.installer
+                "Cannot {} {}", action, installer
             )))
         }
     };
-    let exit_code = output.status.code().unwrap_or(-1);
     if output.status.success() {
-        if output.status.success() {
-            log::info!("Installed {}", parameters.installer);
-            return Ok(())
-        } else {
-            let stdout = String::from_utf8_lossy(&output.stdout[..]);
-            log::error!("Error installing (stdout):\n{}", stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr[..]);
-            log::error!("Error installing (stderr):\n{}", stderr);
-        }
+        log::info!("{} {} finished", action, installer);
+        return Ok(())
     }
+    let stdout = String::from_utf8_lossy(&output.stdout[..]);
+    log::error!("Error on {} (stdout):\n{}", action, stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr[..]);
+    log::error!("Error on {} (stderr):\n{}", action, stderr);
     Err(ChaosError::Other(format!(
-        "Cannot install {}_ exit_status={}", &parameters.installer, exit_code
+        "Cannot {} {}_ exit_status={}", action, installer, output.status.code().unwrap_or(-1)
     )))
 }
 
+pub fn execute_install(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing install");
+    let parameters: InstallParameters = parameters.try_into()?;
+    let file_location = download_file(&parameters.installer)?;
+    let format = PackageFormat::detect(&parameters.installer, &file_location);
+    let mut command = format.install_command(&file_location);
+    apply_install_parameters(&mut command, format, parameters.parameters_mode, &parameters.parameters, &file_location)?;
+    run_package_command(command, "install", &parameters.installer)
+}
+
 pub fn execute_uninstall(parameters: &TestParameters)-> ChaosResult<()> {
     log::info!("Executing uninstall");
     let parameters: InstallParameters = parameters.try_into()?;
-    let mut command = std::process::Command::new(r"dpkg");
     let file_location = download_file(&parameters.installer)?;
-    command
-        .arg("-r")
-        .arg(&file_location.to_string_lossy()[..]);
-    for (param, value) in &parameters.parameters {
-        command.arg(format!("{}={}", param, value));
+    let format = PackageFormat::detect(&parameters.installer, &file_location);
+    let package_name = format.package_name(&file_location)?;
+    let command = format.uninstall_command(&package_name);
+    run_package_command(command, "uninstall", &parameters.installer)
+}
+
+/// Installs the new package over the existing installation
+pub fn execute_upgrade(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing upgrade");
+    let parameters: InstallParameters = parameters.try_into()?;
+    let file_location = download_file(&parameters.installer)?;
+    let format = PackageFormat::detect(&parameters.installer, &file_location);
+    let mut command = format.upgrade_command(&file_location);
+    apply_install_parameters(&mut command, format, parameters.parameters_mode, &parameters.parameters, &file_location)?;
+    run_package_command(command, "upgrade", &parameters.installer)
+}
+
+pub fn execute_install_with_error(parameters: &TestParameters) -> ChaosResult<()>{
+    let parameters: InstallWithErrorParameters = parameters.try_into()?;
+    let file_location = download_file(&parameters.installer)?;
+    let format = PackageFormat::detect(&parameters.installer, &file_location);
+    let mut command = format.install_command(&file_location);
+    apply_install_parameters(&mut command, format, parameters.parameters_mode, &parameters.parameters, &file_location)?;
+    let install_status = command.status().map_err(|e| ChaosError::Other(format!("Cannot install {}: {}", parameters.installer, e)))?.code().unwrap_or_default();
+    if parameters.error != install_status {
+        return Err(ChaosError::Other(format!("expected exit code {}, got {}", parameters.error, install_status)))
     }
-    let output = match command.output() {
-        Ok(v) => v,
-        Err(_) => {
-            return Err(ChaosError::Other(format!(
-                "Cannot uninstall {}", &parameters.installer
-            )))
+    Ok(())
+}
+
+/// Starts the installer, kills it after the delay or when the interrupt file appears and then checks
+/// that the package manager recovers: a reinstall succeeds and dpkg --audit (rpm -V) reports nothing
+pub fn execute_install_interrupted(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing interrupted install");
+    let parameters: InstallInterruptedParameters = parameters.try_into()?;
+    let signal = parse_signal(&parameters.signal)?;
+    let installer = &parameters.install.installer;
+    let file_location = download_file(installer)?;
+    let format = PackageFormat::detect(installer, &file_location);
+    let mut command = format.install_command(&file_location);
+    apply_install_parameters(&mut command, format, parameters.install.parameters_mode, &parameters.install.parameters, &file_location)?;
+    // Own process group to interrupt the maintainer scripts too, like a power loss
+    command.process_group(0).stdout(Stdio::null()).stderr(Stdio::null());
+    let mut child = command.spawn().map_err(|e| ChaosError::Other(format!("Cannot install {}: {}", installer, e)))?;
+    let started = Instant::now();
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(ChaosError::Other(format!("Installer of {} finished before being interrupted: exit_status={}", installer, status.code().unwrap_or(-1))))
         }
+        let file_appeared = parameters.file.as_ref().map(|v| Path::new(v).exists()).unwrap_or(false);
+        if file_appeared || started.elapsed() >= parameters.delay {
+            break
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    log::info!("Interrupting installer of {} with {}", installer, signal);
+    if let Err(e) = killpg(Pid::from_raw(child.id() as i32), signal) {
+        log::warn!("Cannot interrupt installer: {}", e);
+    }
+    let _ = child.wait();
+    let mut command = format.upgrade_command(&file_location);
+    if format == PackageFormat::Rpm {
+        command.arg("--replacepkgs");
+    }
+    apply_install_parameters(&mut command, format, parameters.install.parameters_mode, &parameters.install.parameters, &file_location)?;
+    run_package_command(command, "reinstall", installer)?;
+    let audit = match format {
+        PackageFormat::Deb => package_command_output(Command::new("dpkg").arg("--audit"), installer)?,
+        PackageFormat::Rpm => package_command_output(Command::new("rpm").arg("-V").arg(format.package_name(&file_location)?), installer)?,
     };
-    let exit_code = output.status.code().unwrap_or(-1);
-    if output.status.success() {
-        if output.status.success() {
-            log::info!("Uninstalled {}", parameters.installer);
-            return Ok(())
-        } else {
-            let stdout = String::from_utf8_lossy(&output.stdout[..]);
-            log::error!("Error uninstalling (stdout):\n{}", stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr[..]);
-            log::error!("Error uninstalling (stderr):\n{}", stderr);
+    if !audit.trim().is_empty() {
+        return Err(ChaosError::Other(format!("Package manager state not recovered after reinstall: {}", audit.trim())))
+    }
+    Ok(())
+}
+
+/// Static checks of a Debian package: control file, maintainer scripts, conffiles and installed files.
+/// The inspection is uploaded as an artifact and the problems found make the action fail
+pub fn execute_inspect(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing package inspection");
+    let installer = get_installer_field(parameters)?;
+    let file_location = download_file(&installer)?;
+    if PackageFormat::detect(&installer, &file_location) != PackageFormat::Deb {
+        return Err(ChaosError::Other(format!("Cannot inspect {}: only Debian packages are supported", installer)))
+    }
+    let control = package_command_output(Command::new("dpkg-deb").arg("-f").arg(&file_location), &installer)?;
+    let contents = package_command_output(Command::new("dpkg-deb").arg("-c").arg(&file_location), &installer)?;
+    let control_dir = create_file_in_temp(&format!("inspect-{}", installer));
+    let _ = std::fs::remove_dir_all(&control_dir);
+    std::fs::create_dir_all(&control_dir)?;
+    let extracted = package_command_output(Command::new("dpkg-deb").arg("-e").arg(&file_location).arg(&control_dir), &installer);
+    let mut inspection = PackageInspection {
+        installer : installer.clone(),
+        control : parse_control_file(&control),
+        files : parse_package_contents(&contents),
+        ..Default::default()
+    };
+    if extracted.is_ok() {
+        for script in MAINTAINER_SCRIPTS {
+            if let Ok(content) = std::fs::read_to_string(control_dir.join(script)) {
+                inspection.scripts.insert(script.to_string(), content);
+            }
         }
+        inspection.conffiles = std::fs::read_to_string(control_dir.join("conffiles"))
+            .unwrap_or_default()
+            .lines()
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string())
+            .collect();
     }
-    Err(ChaosError::Other(format!(
-        "Cannot uninstall {}_ exit_status={}", &parameters.installer, exit_code
-    )))
+    let _ = std::fs::remove_dir_all(&control_dir);
+    extracted?;
+    inspection.check();
+    let content = serde_json::to_vec_pretty(&inspection).map_err(|e| ChaosError::Other(format!("Cannot serialize inspection of {}: {}", installer, e)))?;
+    if let Err(e) = upload_data(&format!("inspect-{}.json", installer), content) {
+        log::warn!("Cannot upload package inspection: {}", e);
+    }
+    if inspection.problems.is_empty() {
+        return Ok(())
+    }
+    Err(ChaosError::Other(format!("Packaging problems in {}: {}", installer, inspection.problems.join("; "))))
 }
 
-pub fn execute_install_with_error(parameters: &TestParameters) -> ChaosResult<()>{
-    let parameters: InstallWithErrorParameters = parameters.try_into()?;
-    let mut command = std::process::Command::new(r"dpkg");
-    let file_location = download_file(&parameters.installer)?;
-    command
-        .arg("-i")
-        .arg(&file_location.to_string_lossy()[..]);
-    for (param, value) in &parameters.parameters {
-        command.arg(format!("{}={}", param, value));
-    }
-    let install_status = command.status().unwrap().code().unwrap_or_default();
-    assert_eq!(parameters.error, install_status);
-    Ok(())
+/// Stdout of a package manager command. Fails if the command fails
+fn package_command_output(command: &mut Command, installer: &str) -> ChaosResult<String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command.output().map_err(|e| ChaosError::Other(format!("Cannot execute {} for {}: {}", program, installer, e)))?;
+    if !output.status.success() {
+        return Err(ChaosError::Other(format!("{} failed for {}: {}", program, installer, String::from_utf8_lossy(&output.stderr).trim())))
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
 fn list_deb_packages() -> String {
@@ -151,9 +396,86 @@ fn rpm_package_with_name<'a>(name: &'a str, data: &'a str) -> Option<&'a str> {
     None
 }
 
+/// Version column of the package in the output of dpkg -l. Only installed packages (ii, hi): rc keeps the old version after the uninstall
+fn deb_package_version<'a>(name: &str, data: &'a str) -> Option<&'a str> {
+    for line in data.lines() {
+        let mut columns = line.split_whitespace();
+        match columns.next() {
+            Some("ii" | "hi") => {},
+            _ => continue,
+        }
+        let package = match columns.next() {
+            Some(v) => v,
+            None => continue,
+        };
+        // Multiarch packages: name:amd64
+        if package != name && package.split(':').next() != Some(name) {
+            continue;
+        }
+        return columns.next();
+    }
+    None
+}
+
+/// Version and release of the package in the output of rpm -qa: name-version-release.arch
+fn rpm_package_version<'a>(name: &str, data: &'a str) -> Option<(&'a str, &'a str)> {
+    for line in data.lines() {
+        let line = line.trim();
+        let (rest, release) = match line.rsplit_once('-') {
+            Some(v) => v,
+            None => continue,
+        };
+        let (package, version) = match rest.rsplit_once('-') {
+            Some(v) => v,
+            None => continue,
+        };
+        if package != name {
+            continue;
+        }
+        let release = release.rsplit_once('.').map(|(release, _arch)| release).unwrap_or(release);
+        return Some((version, release));
+    }
+    None
+}
+
+/// The expected version can be the full version or the upstream one without epoch and revision
+fn version_matches(expected: &str, version: &str) -> bool {
+    let expected = expected.trim();
+    if expected == version {
+        return true;
+    }
+    let upstream = version.split_once(':').map(|(_epoch, v)| v).unwrap_or(version);
+    if expected == upstream {
+        return true;
+    }
+    let upstream = upstream.rsplit_once('-').map(|(v, _revision)| v).unwrap_or(upstream);
+    expected == upstream
+}
+
+fn check_installed_version(product_name: &str, expected_version: &str) -> ChaosResult<()> {
+    let rpm_packages = list_rpm_packages();
+    if let Some((version, release)) = rpm_package_version(product_name, &rpm_packages) {
+        if version_matches(expected_version, version) || version_matches(expected_version, &format!("{}-{}", version, release)) {
+            return Ok(())
+        }
+        return Err(ChaosError::Other(format!("Installed version {}-{} does not match expected {}", version, release, expected_version)))
+    }
+    let deb_packages = list_deb_packages();
+    if let Some(version) = deb_package_version(product_name, &deb_packages) {
+        if version_matches(expected_version, version) {
+            return Ok(())
+        }
+        return Err(ChaosError::Other(format!("Installed version {} does not match expected {}", version, expected_version)))
+    }
+    Err(ChaosError::Other("Product is not installed".into()))
+}
+
 /// Returns Ok(()) when the product IS installed
 pub fn check_installed(parameters: &TestParameters) -> ChaosResult<()>{
     let parameters: InstallCheckParameters = parameters.try_into()?;
+    if let Some(expected_version) = &parameters.expected_version {
+        return check_installed_version(&parameters.product_name, expected_version)
+    }
     let rpm_packages = list_rpm_packages();
     if let Some(_rpm) = rpm_package_with_name(&parameters.product_name, &rpm_packages) {
         return Ok(())
@@ -245,4 +567,50 @@ google-noto-cjk-fonts-common-20230817-2.el9.noarch
 "#;
     let package_name = rpm_package_with_name("chaos-bench", packages).unwrap();
     assert_eq!("chaos-bench", package_name);
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_extract_installed_package_versions() {
+    let packages = r#"||/ Name                          Version                                 Architecture Description
++++-=============================-=======================================-============-================================================================================
+ii  chaos-bench-tools             0.0.9                                   amd64        ChaosBench tools
+ii  chaos-bench                   1:0.1.0-2                               amd64        ChaosBench
+ii  linux-libc-dev:amd64          5.15.0-117.127                          amd64        Linux Kernel Headers for development
+
+rc  chaos-bench-agent             0.0.8                                   amd64        ChaosBench agent
+hi  chaos-bench-pinned            0.0.7                                   amd64        ChaosBench pinned
+"#;
+    assert_eq!(Some("1:0.1.0-2"), deb_package_version("chaos-bench", packages));
+    assert_eq!(Some("5.15.0-117.127"), deb_package_version("linux-libc-dev", packages));
+    assert_eq!(None, deb_package_version("chaos-bench-agent", packages));
+    assert_eq!(Some("0.0.7"), deb_package_version("chaos-bench-pinned", packages));
+    assert!(version_matches("0.1.0", "1:0.1.0-2"));
+    assert!(version_matches("0.1.0-2", "1:0.1.0-2"));
+    assert!(!version_matches("0.1.1", "1:0.1.0-2"));
+
+    let packages = r#"chaos-bench-tools-0.0.9-1.el9.x86_64
+chaos-bench-0.1.0~rc1-1.el9.x86_64
+"#;
+    assert_eq!(Some(("0.1.0~rc1", "1.el9")), rpm_package_version("chaos-bench", packages));
+}
+
+#[test]
+fn should_detect_package_format_from_magic_bytes() {
+    assert_eq!(Some(PackageFormat::Rpm), PackageFormat::from_magic(&[0xED, 0xAB, 0xEE, 0xDB, 0x03, 0x00, 0x00, 0x01]));
+    assert_eq!(Some(PackageFormat::Deb), PackageFormat::from_magic(b"!<arch>\n"));
+    assert_eq!(None, PackageFormat::from_magic(b"PK\x03\x04\x00\x00\x00\x00"));
+}
+
+#[test]
+fn should_render_debconf_preseed() {
+    let mut parameters = BTreeMap::new();
+    parameters.insert("SERVER".to_string(), "https://127.0.0.1".to_string());
+    parameters.insert("shared/accepted-license:boolean".to_string(), "true".to_string());
+    parameters.insert("MODE:select".to_string(), "agent".to_string());
+    parameters.insert("URL:https".to_string(), "x".to_string());
+    let preseed = debconf_preseed("chaos-bench", &parameters);
+    assert_eq!("chaos-bench chaos-bench/MODE select agent\n\
+        chaos-bench chaos-bench/SERVER string https://127.0.0.1\n\
+        chaos-bench chaos-bench/URL:https string x\n\
+        chaos-bench shared/accepted-license boolean true\n", preseed);
+}
@@ -14,8 +14,11 @@ pub fn package_action(action : &PackageActionType, parameters: &TestParameters)
     match action {
         PackageActionType::Install => execute_install(parameters),
         PackageActionType::Uninstall => execute_uninstall(parameters),
+        PackageActionType::Upgrade => execute_upgrade(parameters),
         PackageActionType::InstallWithError => execute_install_with_error(parameters),
         PackageActionType::IsInstalled => check_installed(parameters),
         PackageActionType::IsNotInstalled => check_not_installed(parameters),
+        PackageActionType::Inspect => execute_inspect(parameters),
+        PackageActionType::InstallInterrupted => execute_install_interrupted(parameters),
     }
 }
\ No newline at end of file
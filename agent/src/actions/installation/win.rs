@@ -1,4 +1,4 @@
-use chaos_core::{action::install::{InstallCheckParameters, InstallParameters, InstallWithErrorParameters}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use chaos_core::{action::install::{InstallCheckParameters, InstallInterruptedParameters, InstallParameters, InstallWithErrorParameters}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
 use windows::Win32::System::Registry::HKEY_LOCAL_MACHINE;
 
 use crate::{api::download_file, common::create_file_path_in_workspace, reg::{self, RegValue}};
@@ -40,6 +40,32 @@ pub fn execute_uninstall(parameters: &TestParameters)-> ChaosResult<()> {
     Ok(())
 }
 
+/// Upgrades the installed MSI with a newer one
+pub fn execute_upgrade(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing upgrade");
+    let parameters: InstallParameters = parameters.try_into()?;
+    let mut command = std::process::Command::new(r"C:\Windows\System32\msiexec.exe");
+    let log_location = create_file_path_in_workspace("upgrade.log");
+    let file_location = download_file(&parameters.installer)?;
+    command
+        .arg("/i")
+        .arg(&file_location.to_string_lossy()[..])
+        .arg("REINSTALL=ALL")
+        .arg("REINSTALLMODE=vomus")
+        .arg("/qn")
+        .arg("/l*v")
+        .arg(log_location.as_os_str());
+    for (param, value) in &parameters.parameters {
+        command.arg(format!("{}={}", param, value));
+    }
+    let status = command.status().map_err(|e| ChaosError::Other(format!("Cannot upgrade {}: {}", parameters.installer, e)))?.code().unwrap_or_default();
+    if status != 0 && status != 3010 {
+        return Err(ChaosError::Other(format!("Cannot upgrade {}: {}", parameters.installer, parse_msi_result(status))))
+    }
+    log::info!("Upgraded {}", parameters.installer);
+    Ok(())
+}
+
 pub fn execute_install_with_error(parameters: &TestParameters) -> ChaosResult<()>{
     let parameters: InstallWithErrorParameters = parameters.try_into()?;
     let mut command = std::process::Command::new(r"C:\Windows\System32\msiexec.exe");
@@ -60,6 +86,52 @@ pub fn execute_install_with_error(parameters: &TestParameters) -> ChaosResult<()
     Ok(())
 }
 
+/// Terminates msiexec after the delay or when the interrupt file appears and then checks that a reinstall succeeds.
+/// The signal is ignored: the process is always terminated
+pub fn execute_install_interrupted(parameters: &TestParameters) -> ChaosResult<()> {
+    log::info!("Executing interrupted install");
+    let parameters: InstallInterruptedParameters = parameters.try_into()?;
+    let installer = &parameters.install.installer;
+    let file_location = download_file(installer)?;
+    let msi_command = |log_name : &str| {
+        let mut command = std::process::Command::new(r"C:\Windows\System32\msiexec.exe");
+        command
+            .arg("/i")
+            .arg(&file_location.to_string_lossy()[..])
+            .arg("/qn")
+            .arg("/l*v")
+            .arg(create_file_path_in_workspace(log_name).as_os_str());
+        for (param, value) in &parameters.install.parameters {
+            command.arg(format!("{}={}", param, value));
+        }
+        command
+    };
+    let mut child = msi_command("install_interrupted.log").spawn().map_err(|e| ChaosError::Other(format!("Cannot install {}: {}", installer, e)))?;
+    let started = std::time::Instant::now();
+    loop {
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(ChaosError::Other(format!("Installer of {} finished before being interrupted: {}", installer, parse_msi_result(status.code().unwrap_or_default()))))
+        }
+        let file_appeared = parameters.file.as_ref().map(|v| std::path::Path::new(v).exists()).unwrap_or(false);
+        if file_appeared || started.elapsed() >= parameters.delay {
+            break
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    let status = msi_command("reinstall.log").status().map_err(|e| ChaosError::Other(format!("Cannot reinstall {}: {}", installer, e)))?.code().unwrap_or_default();
+    if status != 0 && status != 3010 {
+        return Err(ChaosError::Other(format!("Cannot reinstall {} after interruption: {}", installer, parse_msi_result(status))))
+    }
+    Ok(())
+}
+
+/// MSI tables are not inspected
+pub fn execute_inspect(_parameters: &TestParameters) -> ChaosResult<()> {
+    Err(ChaosError::Other("Package inspection is not supported in Windows".into()))
+}
+
 /// Returns Ok(()) when the product IS installed
 pub fn check_installed(parameters: &TestParameters) -> ChaosResult<()>{
     let parameters: InstallCheckParameters = parameters.try_into()?;
@@ -71,6 +143,14 @@ pub fn check_installed(parameters: &TestParameters) -> ChaosResult<()>{
             }
         }
     }
+    if let Some(expected_version) = &parameters.expected_version {
+        let version = product_version_in_uninstall_registry(&parameters.product_name, &registry)?;
+        return match version {
+            Some(version) if version.trim() == expected_version.trim() => Ok(()),
+            Some(version) => Err(ChaosError::Other(format!("Installed version {} does not match expected {}", version, expected_version))),
+            None => Err(ChaosError::Other(format!("Product is not installed")))
+        }
+    }
     let installed = check_if_product_name_in_uninstall_registry(&parameters.product_name, &registry)?;
     if installed {
         return Ok(())
@@ -110,6 +190,39 @@ fn check_if_product_code_in_uninstall_registry(product_code : &str, registry : &
     check_if_code_in_uninstall_registry(product_code, registry, r"SOFTWARE\Wow6432Node\Microsoft\Windows\CurrentVersion\Uninstall")
 }
 
+fn product_version_in_uninstall_registry(product_name : &str, registry : &reg::RegistryEditor) -> ChaosResult<Option<String>>{
+    if let Ok(Some(v)) = version_in_uninstall_registry(product_name, registry, r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall") {
+        return Ok(Some(v))
+    }
+    version_in_uninstall_registry(product_name, registry, r"SOFTWARE\Wow6432Node\Microsoft\Windows\CurrentVersion\Uninstall")
+}
+
+fn version_in_uninstall_registry(product_name : &str, registry : &reg::RegistryEditor, uninstall_key : &str) -> ChaosResult<Option<String>>{
+    let product_name = product_name.trim();
+    let uninstall_key = registry.open_key(HKEY_LOCAL_MACHINE, uninstall_key)?;
+    let subkeys = registry.enumerate_keys(uninstall_key)?;
+    for key in subkeys {
+        let hkey = match registry.open_key(uninstall_key, &key) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        let is_product = matches!(registry.read_value(hkey, "DisplayName"), Ok(RegValue::SZ(display_name)) if display_name.trim() == product_name);
+        if !is_product {
+            registry.close_key(hkey);
+            continue
+        }
+        let version = match registry.read_value(hkey, "DisplayVersion") {
+            Ok(RegValue::SZ(version)) => Some(version),
+            _ => None
+        };
+        registry.close_key(hkey);
+        registry.close_key(uninstall_key);
+        return Ok(version)
+    }
+    registry.close_key(uninstall_key);
+    Ok(None)
+}
+
 fn check_if_code_in_uninstall_registry(product_code : &str, registry : &reg::RegistryEditor, uninstall_key : &str) -> ChaosResult<bool>{
     let product_code = product_code.trim();
     let uninstall_key = registry.open_key(HKEY_LOCAL_MACHINE, uninstall_key)?;
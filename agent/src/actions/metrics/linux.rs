@@ -2,7 +2,7 @@ use std::{io::Read, sync::{atomic::{AtomicBool, Ordering}, Arc}};
 
 use chaos_core::{action::metrics::{StartMetricsForProcess, StartMetricsForService}, err::{ChaosError, ChaosResult}};
 
-use crate::{actions::metrics::{add_process_metric, add_service_metric}, common::spawn_child_and_return_stdout};
+use crate::{actions::metrics::{add_process_counters, add_process_metric, add_service_counters, add_service_metric, ProcessCounters}, common::spawn_child_and_return_stdout};
 
 pub fn spawn_metrics_for_process(parameters : StartMetricsForProcess, stopper : Arc<AtomicBool>) {
     std::thread::spawn(move || {
@@ -71,6 +71,10 @@ pub fn spawn_metrics_for_process(parameters : StartMetricsForProcess, stopper :
             let cpu = user_util + sys_util;
             log::info!("Metrics of process {pid}: CPU={cpu} RAM={ram}");
             add_process_metric(&parameters.executable_path, (ram, cpu));
+            match read_process_counters(pid) {
+                Ok(v) => add_process_counters(&parameters.executable_path, v),
+                Err(e) => log::warn!("{}", e)
+            }
             std::thread::sleep(parameters.sampling_frequency);
         }
     });
@@ -144,6 +148,10 @@ pub fn spawn_metrics_for_service(parameters : StartMetricsForService, stopper :
             let cpu = user_util + sys_util;
             log::info!("Metrics of process {pid}: CPU={cpu} RAM={ram}");
             add_service_metric(&parameters.service_name, (ram, cpu));
+            match read_process_counters(pid) {
+                Ok(v) => add_service_counters(&parameters.service_name, v),
+                Err(e) => log::warn!("{}", e)
+            }
             std::thread::sleep(parameters.sampling_frequency);
         }
     });
@@ -154,6 +162,7 @@ fn sleep_error() {
 }
 
 fn extract_process_cpu_times(process : u32, process_times : &mut Vec<u32>, buffer : &mut String) -> ChaosResult<(u32, u32)> {
+    buffer.clear();
     let res : std::io::Result<usize> = (|| {
         let mut f = std::fs::File::open(format!("/proc/{}/stat", process))?;
         f.read_to_string(buffer)
@@ -171,6 +180,7 @@ fn extract_process_cpu_times(process : u32, process_times : &mut Vec<u32>, buffe
 }
 
 fn extract_process_ram_usage(pid : u32, buffer : &mut String)-> ChaosResult<u64> {
+    buffer.clear();
     let res : std::io::Result<usize> = (|| {
         let mut f = std::fs::File::open(format!("/proc/{}/status", pid))?;
         f.read_to_string(buffer)
@@ -187,6 +197,7 @@ fn extract_process_ram_usage(pid : u32, buffer : &mut String)-> ChaosResult<u64>
 }
 
 fn extract_cpu_times(cpu_times : &mut Vec<u32>, buffer : &mut String) -> ChaosResult<(u32, u32)> {
+    buffer.clear();
     let res : std::io::Result<usize> = (|| {
         let mut f = std::fs::File::open("/proc/stat")?;
         f.read_to_string(buffer)
@@ -277,14 +288,48 @@ fn parse_process_ram_usage(line : &str) -> Option<u64> {
     }
 }
 
-fn get_pid_of_service(name : &str) -> ChaosResult<u32> {
+/// File descriptors, sockets, threads, I/O and context switches of /proc/<pid>
+fn read_process_counters(pid : u32) -> ChaosResult<ProcessCounters> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).map_err(|e| ChaosError::Other(format!("Cannot read status of process {}: {}", pid, e)))?;
+    // Only readable by the owner of the process or root
+    let io = std::fs::read_to_string(format!("/proc/{}/io", pid)).unwrap_or_default();
+    let mut counters = ProcessCounters {
+        threads : parse_proc_field(&status, "Threads").unwrap_or_default(),
+        voluntary_switches : parse_proc_field(&status, "voluntary_ctxt_switches").unwrap_or_default(),
+        involuntary_switches : parse_proc_field(&status, "nonvoluntary_ctxt_switches").unwrap_or_default(),
+        read_bytes : parse_proc_field(&io, "read_bytes").unwrap_or_default(),
+        write_bytes : parse_proc_field(&io, "write_bytes").unwrap_or_default(),
+        ..Default::default()
+    };
+    let fds = std::fs::read_dir(format!("/proc/{}/fd", pid)).map_err(|e| ChaosError::Other(format!("Cannot list file descriptors of process {}: {}", pid, e)))?;
+    for fd in fds.flatten() {
+        counters.fds += 1;
+        if std::fs::read_link(fd.path()).map(|v| v.to_string_lossy().starts_with("socket:")).unwrap_or(false) {
+            counters.sockets += 1;
+        }
+    }
+    Ok(counters)
+}
+
+/// Value of a "name: value" line of /proc/<pid>/status or /proc/<pid>/io
+fn parse_proc_field(content : &str, name : &str) -> Option<u64> {
+    content.lines().find_map(|line| {
+        let (field, value) = line.split_once(':')?;
+        if field.trim() != name {
+            return None
+        }
+        value.split_whitespace().next()?.parse().ok()
+    })
+}
+
+pub(crate) fn get_pid_of_service(name : &str) -> ChaosResult<u32> {
     let mut cmd = std::process::Command::new("systemctl");
     cmd.arg("show").arg("--property").arg("MainPID").arg(name);
     let stdout = spawn_child_and_return_stdout(cmd, std::time::Duration::from_secs_f32(10.0), "Cannot extract PID of service using systemctl")?;
     parse_pid_of_service(&stdout)
 }
 
-fn get_pid_of_process_by_name(name : &str) -> ChaosResult<u32> {
+pub(crate) fn get_pid_of_process_by_name(name : &str) -> ChaosResult<u32> {
     let process_list = get_running_process_ids()?;
     for pid in process_list {
         let pth = match get_exe_of_pid(pid) {
@@ -306,7 +351,7 @@ fn get_exe_of_pid(pid : u32) -> ChaosResult<String> {
     Ok(ex.to_string_lossy().to_string())
 }
 
-fn get_running_process_ids() -> ChaosResult<Vec<u32>> {
+pub(crate) fn get_running_process_ids() -> ChaosResult<Vec<u32>> {
     Ok(std::fs::read_dir("/proc").map_err(|e| ChaosError::Other(format!("Cannot list running processes: {}", e)))?        
         .filter_map(Result::ok)
         .filter_map(|entry| entry.file_name().into_string().ok())
@@ -355,4 +400,18 @@ fn should_parse_process_times() {
 fn should_parse_service_pid() {
     let stdout = r#"MainPID=125688"#;
     assert_eq!(125688, parse_pid_of_service(stdout).unwrap());
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_parse_proc_fields() {
+    let status = "Name:\tnode\nThreads:\t7\nvoluntary_ctxt_switches:\t150\nnonvoluntary_ctxt_switches:\t12\n";
+    assert_eq!(Some(7), parse_proc_field(status, "Threads"));
+    assert_eq!(Some(150), parse_proc_field(status, "voluntary_ctxt_switches"));
+    assert_eq!(Some(12), parse_proc_field(status, "nonvoluntary_ctxt_switches"));
+    let io = "rchar: 323934931\nwchar: 323929600\nread_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0\n";
+    assert_eq!(Some(4096), parse_proc_field(io, "read_bytes"));
+    assert_eq!(Some(323932160), parse_proc_field(io, "write_bytes"));
+    assert_eq!(None, parse_proc_field(io, "syscr"));
+    let counters = read_process_counters(std::process::id()).unwrap();
+    assert!(counters.fds > 0 && counters.threads > 0);
+}
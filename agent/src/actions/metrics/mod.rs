@@ -1,8 +1,8 @@
 #[cfg(target_os="windows")]
 pub mod win;
-use std::{cell::RefCell, collections::{BTreeMap, VecDeque}, sync::{atomic::{AtomicBool, Ordering}, Arc}, time::Duration};
+use std::{collections::{BTreeMap, VecDeque}, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}, time::Duration};
 
-use chaos_core::{action::{metrics::{MetricsArtifact, StartMetricsForProcess, StartMetricsForService, StopMetricsForProcess, StopMetricsForService, UploadMetricsForProcess, UploadMetricsForService}, MetricActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use chaos_core::{action::{metrics::{format_metric_stats, AssertNoLeakParameters, LinearTrend, MetricStats, MetricThreshold, MetricsArtifact, StartMetricsForProcess, StartMetricsForService, StopMetricsForProcess, StopMetricsForService, UploadMetricsForProcess, UploadMetricsForService}, MetricActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
 #[cfg(target_os="windows")]
 pub use win::*;
 
@@ -13,25 +13,41 @@ pub use linux::*;
 
 use crate::common::now_milliseconds;
 
-thread_local! {
-    pub static SERVICE_THREADS: RefCell<BTreeMap<String, (Arc<AtomicBool>, MetricCalculator)>> = const { RefCell::new(BTreeMap::new()) };
-    pub static PROCESS_THREADS: RefCell<BTreeMap<String, (Arc<AtomicBool>, MetricCalculator)>> = const { RefCell::new(BTreeMap::new()) };
-}
+type MetricRegistry = Mutex<BTreeMap<String, (Arc<AtomicBool>, MetricCalculator)>>;
+
+/// Shared with the sampling threads
+pub static SERVICE_THREADS: MetricRegistry = Mutex::new(BTreeMap::new());
+pub static PROCESS_THREADS: MetricRegistry = Mutex::new(BTreeMap::new());
 
-pub fn metric_action(action : &MetricActionType, parameters: &TestParameters) -> ChaosResult<()> {
+/// Statistics of each metric of the stopped metrics
+pub fn metric_action(action : &MetricActionType, parameters: &TestParameters) -> ChaosResult<Option<BTreeMap<String, MetricStats>>> {
     match action {
-        MetricActionType::StartMetricsForProcess => start_metric_for_process(parameters),
-        MetricActionType::StopMetricsForProcess => stop_metric_for_process(parameters),
-        MetricActionType::UploadProcessMetrics => upload_metric_for_process(parameters),
-        MetricActionType::StartMetricsForService => start_metric_for_service(parameters),
-        MetricActionType::StopMetricsForService => stop_metric_for_service(parameters),
-        MetricActionType::UploadServiceMetrics => upload_metric_for_service(parameters),
+        MetricActionType::StartMetricsForProcess => start_metric_for_process(parameters).map(|_| None),
+        MetricActionType::StopMetricsForProcess => stop_metric_for_process(parameters).map(Some),
+        MetricActionType::UploadProcessMetrics => upload_metric_for_process(parameters).map(|_| None),
+        MetricActionType::StartMetricsForService => start_metric_for_service(parameters).map(|_| None),
+        MetricActionType::StopMetricsForService => stop_metric_for_service(parameters).map(Some),
+        MetricActionType::UploadServiceMetrics => upload_metric_for_service(parameters).map(|_| None),
+        MetricActionType::AssertNoLeak => assert_no_leak(parameters).map(|_| None),
     }
 }
 
-struct MetricCalculator {
+/// Counters of a process. I/O bytes and context switches are totals since the process started
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessCounters {
+    pub fds : u64,
+    pub threads : u64,
+    pub sockets : u64,
+    pub read_bytes : u64,
+    pub write_bytes : u64,
+    pub voluntary_switches : u64,
+    pub involuntary_switches : u64
+}
+
+pub struct MetricCalculator {
     pub cpu_samples : VecDeque<f32>,
     pub ram_samples : VecDeque<u64>,
+    pub counters : VecDeque<ProcessCounters>,
     pub start_time : i64,
     pub freq : Duration
 }
@@ -41,6 +57,7 @@ impl MetricCalculator {
         Self {
             cpu_samples : VecDeque::with_capacity(128),
             ram_samples : VecDeque::with_capacity(128),
+            counters : VecDeque::with_capacity(128),
             freq,
             start_time : now_milliseconds()
         }
@@ -49,35 +66,21 @@ impl MetricCalculator {
         self.cpu_samples.push_back(cpu);
         self.ram_samples.push_back(ram);
     }
-
-    pub fn calculate(&self) -> (u64, f32) {
-        let samples = self.cpu_samples.len();
-        let mut total_cpu : f64 = 0.0;
-        for cpu in self.cpu_samples.iter() {
-            total_cpu += *cpu as f64;
-        }
-        let avg_cpu = (total_cpu / (samples.max(1) as f64)) as f32;
-        let mut samples = self.ram_samples.len();
-        let mut total_ram : u64 = 0;
-        for ram in self.ram_samples.iter() {
-            let (ram, smpls) = match total_ram.checked_add(*ram) {
-                Some(v) => (v, samples + 1),
-                None => {
-                    let avg_ram = total_ram / (samples as u64);
-                    (avg_ram + ram, 2)
-                }
-            };
-            samples = smpls;
-            total_ram += ram;
-        }
-        
-        (total_ram / samples.max(1) as u64, avg_cpu)
+    pub fn add_counters(&mut self, counters : ProcessCounters) {
+        self.counters.push_back(counters);
     }
 
     pub fn full_metrics(&self) -> MetricsArtifact {
         MetricsArtifact {
             ram_samples: self.ram_samples.iter().copied().collect(),
             cpu_samples: self.cpu_samples.iter().map(|v| *v as f64).collect(),
+            fd_samples: self.counters.iter().map(|v| v.fds).collect(),
+            thread_samples: self.counters.iter().map(|v| v.threads).collect(),
+            socket_samples: self.counters.iter().map(|v| v.sockets).collect(),
+            read_bytes_samples: deltas(&self.counters, |v| v.read_bytes),
+            write_bytes_samples: deltas(&self.counters, |v| v.write_bytes),
+            voluntary_switch_samples: deltas(&self.counters, |v| v.voluntary_switches),
+            involuntary_switch_samples: deltas(&self.counters, |v| v.involuntary_switches),
             start_time: self.start_time,
             end_time: now_milliseconds(),
             freq: self.freq,
@@ -85,34 +88,37 @@ impl MetricCalculator {
     }
 }
 
+/// Increase of a total since the previous sample. The total of a restarted process starts again from 0
+fn deltas(counters : &VecDeque<ProcessCounters>, total : impl Fn(&ProcessCounters) -> u64) -> Vec<u64> {
+    let mut previous = None;
+    counters.iter().map(|v| {
+        let current = total(v);
+        let delta = match previous {
+            Some(previous) if current >= previous => current - previous,
+            Some(_) => current,
+            None => 0
+        };
+        previous = Some(current);
+        delta
+    }).collect()
+}
+
 
 pub fn start_metric_for_service(parameters: &TestParameters) -> ChaosResult<()> {
     let parameters: StartMetricsForService = parameters.try_into()?;
     let stopper = Arc::new(AtomicBool::new(true));
     let service_name = parameters.service_name.clone();
     let stpr = stopper.clone();
-    SERVICE_THREADS.with_borrow_mut(|v| {
-        v.insert(service_name, (stpr, MetricCalculator::new(parameters.sampling_frequency)));
-    });
+    SERVICE_THREADS.lock().unwrap().insert(service_name, (stpr, MetricCalculator::new(parameters.sampling_frequency)));
 
     spawn_metrics_for_service(parameters, stopper);
     Ok(())
 }
 
-pub fn stop_metric_for_service(parameters: &TestParameters) -> ChaosResult<()> {
+pub fn stop_metric_for_service(parameters: &TestParameters) -> ChaosResult<BTreeMap<String, MetricStats>> {
     let parameters: StopMetricsForService = parameters.try_into()?;
-    let metric = SERVICE_THREADS.with_borrow_mut(|v| {
-        if let Some(v) = v.get(&parameters.service_name) { v.0.store(false, Ordering::Relaxed) }
-        v.remove(&parameters.service_name).unwrap()
-    });
-    let avg_metrics = metric.1.calculate();
-    if avg_metrics.0 > parameters.max_average_ram {
-        return Err(chaos_core::err::ChaosError::Other(format!("Average RAM usage larger than expected: {} vs {}", avg_metrics.0, parameters.max_average_ram)));
-    }
-    if avg_metrics.1 > parameters.max_average_cpu {
-        return Err(chaos_core::err::ChaosError::Other(format!("Average CPU usage larger than expected: {} vs {}", avg_metrics.1, parameters.max_average_cpu)));
-    }
-    Ok(())
+    let metric = stop_metric(&SERVICE_THREADS, &parameters.service_name)?;
+    check_thresholds(metric.full_metrics().stats(), &parameters.thresholds)
 }
 
 pub fn start_metric_for_process(parameters: &TestParameters) -> ChaosResult<()> {
@@ -120,35 +126,48 @@ pub fn start_metric_for_process(parameters: &TestParameters) -> ChaosResult<()>
     let stopper = Arc::new(AtomicBool::new(true));
     let executable_path = parameters.executable_path.clone();
     let stpr = stopper.clone();
-    PROCESS_THREADS.with_borrow_mut(|v| {
-        v.insert(executable_path, (stpr, MetricCalculator::new(parameters.sampling_frequency)));
-    });
+    PROCESS_THREADS.lock().unwrap().insert(executable_path, (stpr, MetricCalculator::new(parameters.sampling_frequency)));
     spawn_metrics_for_process(parameters, stopper);
     Ok(())
 }
 
-pub fn stop_metric_for_process(parameters: &TestParameters) -> ChaosResult<()> {
+pub fn stop_metric_for_process(parameters: &TestParameters) -> ChaosResult<BTreeMap<String, MetricStats>> {
     let parameters: StopMetricsForProcess = parameters.try_into()?;
-    let metric = PROCESS_THREADS.with_borrow_mut(|v| {
-        if let Some(v) = v.get(&parameters.executable_path) { v.0.store(false, Ordering::Relaxed) }
-        v.remove(&parameters.executable_path).unwrap()
-    });
-    let avg_metrics = metric.1.calculate();
-    if avg_metrics.0 > parameters.max_average_ram {
-        return Err(chaos_core::err::ChaosError::Other(format!("Average RAM usage larger than expected: {} vs {}", avg_metrics.0, parameters.max_average_ram)));
-    }
-    if avg_metrics.1 > parameters.max_average_cpu {
-        return Err(chaos_core::err::ChaosError::Other(format!("Average CPU usage larger than expected: {} vs {}", avg_metrics.1, parameters.max_average_cpu)));
+    let metric = stop_metric(&PROCESS_THREADS, &parameters.executable_path)?;
+    check_thresholds(metric.full_metrics().stats(), &parameters.thresholds)
+}
+
+/// Trend of the RAM of the process or service being measured
+pub fn assert_no_leak(parameters: &TestParameters) -> ChaosResult<LinearTrend> {
+    let parameters: AssertNoLeakParameters = parameters.try_into()?;
+    let metric = parameters.executable_path.as_ref()
+        .and_then(|path| PROCESS_THREADS.lock().unwrap().get(path).map(|v| v.1.full_metrics()))
+        .or_else(|| parameters.service_name.as_ref().and_then(|service| SERVICE_THREADS.lock().unwrap().get(service).map(|v| v.1.full_metrics())));
+    let metric = metric.ok_or_else(|| ChaosError::Other("Cannot find metric registry".into()))?;
+    let trend = metric.ram_trend(parameters.warmup).ok_or_else(|| ChaosError::Other(format!("Not enough RAM samples after the warm-up: {}", metric.ram_samples.len())))?;
+    log::info!("RAM trend: {:.0} bytes/hour R²={:.2}", trend.slope_per_hour, trend.r_squared);
+    parameters.check(&trend)?;
+    Ok(trend)
+}
+
+fn stop_metric(registry : &MetricRegistry, name : &str) -> ChaosResult<MetricCalculator> {
+    let (stopper, metric) = registry.lock().unwrap().remove(name).ok_or_else(|| ChaosError::Other(format!("Metrics for {} not started", name)))?;
+    stopper.store(false, Ordering::Relaxed);
+    Ok(metric)
+}
+
+/// All the statistics are reported when a threshold is exceeded
+fn check_thresholds(stats : BTreeMap<String, MetricStats>, thresholds : &[MetricThreshold]) -> ChaosResult<BTreeMap<String, MetricStats>> {
+    let exceeded : Vec<String> = thresholds.iter().filter_map(|v| v.check(&stats).err()).collect();
+    if !exceeded.is_empty() {
+        return Err(ChaosError::Other(format!("{}. {}", exceeded.join(", "), format_metric_stats(&stats))))
     }
-    Ok(())
+    Ok(stats)
 }
 
 pub fn upload_metric_for_service(parameters: &TestParameters) -> ChaosResult<()> {
     let parameters: UploadMetricsForService = parameters.try_into()?;
-    let metric : Option<MetricsArtifact> = SERVICE_THREADS.with_borrow_mut(|v| {
-        let metrics = v.get(&parameters.service_name)?;
-        Some(metrics.1.full_metrics())
-    });
+    let metric : Option<MetricsArtifact> = SERVICE_THREADS.lock().unwrap().get(&parameters.service_name).map(|v| v.1.full_metrics());
     let mut metric = match metric  {
         Some(v) => v,
         None => return Err(ChaosError::Other("Cannot find metric registry".into())),
@@ -160,10 +179,7 @@ pub fn upload_metric_for_service(parameters: &TestParameters) -> ChaosResult<()>
 
 pub fn upload_metric_for_process(parameters: &TestParameters) -> ChaosResult<()> {
     let parameters: UploadMetricsForProcess = parameters.try_into()?;
-    let metric : Option<MetricsArtifact> = PROCESS_THREADS.with_borrow_mut(|v| {
-        let metrics = v.get(&parameters.executable_path)?;
-        Some(metrics.1.full_metrics())
-    });
+    let metric : Option<MetricsArtifact> = PROCESS_THREADS.lock().unwrap().get(&parameters.executable_path).map(|v| v.1.full_metrics());
     let mut metric = match metric  {
         Some(v) => v,
         None => return Err(ChaosError::Other("Cannot find metric registry".into())),
@@ -175,16 +191,33 @@ pub fn upload_metric_for_process(parameters: &TestParameters) -> ChaosResult<()>
 }
 
 pub fn add_service_metric(service_name: &str, metrics : (u64, f32)) {
-    SERVICE_THREADS.with_borrow_mut(|v| {
-        v.get_mut(service_name).map(|v| {
-            v.1.add(metrics.0, metrics.1);
-        })
-    });
+    if let Some(v) = SERVICE_THREADS.lock().unwrap().get_mut(service_name) {
+        v.1.add(metrics.0, metrics.1);
+    }
 }
 pub fn add_process_metric(service_name: &str, metrics : (u64, f32)) {
-    PROCESS_THREADS.with_borrow_mut(|v| {
-        v.get_mut(service_name).map(|v| {
-            v.1.add(metrics.0, metrics.1);
-        })
-    });
-}
\ No newline at end of file
+    if let Some(v) = PROCESS_THREADS.lock().unwrap().get_mut(service_name) {
+        v.1.add(metrics.0, metrics.1);
+    }
+}
+pub fn add_service_counters(service_name: &str, counters : ProcessCounters) {
+    if let Some(v) = SERVICE_THREADS.lock().unwrap().get_mut(service_name) {
+        v.1.add_counters(counters);
+    }
+}
+pub fn add_process_counters(executable_path: &str, counters : ProcessCounters) {
+    if let Some(v) = PROCESS_THREADS.lock().unwrap().get_mut(executable_path) {
+        v.1.add_counters(counters);
+    }
+}
+
+#[test]
+fn should_calculate_counter_deltas() {
+    let mut calculator = MetricCalculator::new(Duration::from_secs(1));
+    for (fds, read_bytes) in [(10, 1000), (12, 1500), (11, 1500), (9, 200)] {
+        calculator.add_counters(ProcessCounters { fds, read_bytes, ..Default::default() });
+    }
+    let artifact = calculator.full_metrics();
+    assert_eq!(vec![10, 12, 11, 9], artifact.fd_samples);
+    assert_eq!(vec![0, 500, 0, 200], artifact.read_bytes_samples);
+}
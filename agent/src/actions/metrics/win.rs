@@ -91,7 +91,7 @@ fn filetime_to_u64(filetime : FILETIME) -> u64{
     (filetime.dwHighDateTime as u64) << 32 | filetime.dwLowDateTime as u64
 }
 
-fn get_pid_of_service(name : &str) -> ChaosResult<u32> {
+pub(crate) fn get_pid_of_service(name : &str) -> ChaosResult<u32> {
     let service = open_service(name)?;
     let mut buffer = vec![0; 2048];
     let mut bytest_needed = 0;
@@ -1,6 +1,6 @@
 use std::time::Duration;
 
-use chaos_core::{action::{wait::WaitParameters, ArtifactActionType, TestActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use chaos_core::{action::{names::{AFFECTED_PIDS, METRIC_RAM_TREND, METRIC_STATS, TIME_SKEW}, wait::WaitParameters, ArtifactActionType, MetricActionType, ServiceActionType, TestActionType}, err::{ChaosError, ChaosResult}, parameters::{TestParameter, TestParameters}};
 use execute::command_execution_action;
 
 use crate::{common::{now_milliseconds, AgentTaskInternal}, state::AgentState};
@@ -15,38 +15,53 @@ pub mod metrics;
 pub mod download;
 pub mod execute;
 pub mod dns;
+pub mod snapshot;
+pub mod trace;
+pub mod audit;
+pub mod process;
+pub mod network;
+pub mod firewall;
+pub mod stress;
+pub mod filesystem;
+pub mod time;
+pub mod during;
+pub mod soak;
 
 /// Ejecutar una acción que viene desde el servidor, la idea es que esto produzca un TaskResult que se pueda enviar de vuelta al servidor
 /// Además es necesario guardar el estado de la operación en una bbdd local, así como también la sobreescritura de acciones.
 pub fn execute_action(origin_action : TestActionType, state : &mut AgentState, task : &mut AgentTaskInternal) -> ChaosResult<()> {
-    let global_parameters = state.db.get_global_parameters();
-    let commands = state.db.get_commands();
-    let mut parameters: TestParameters = global_parameters.into();
-    let mut action = origin_action.clone();
     task.retries -= 1;
-    if let TestActionType::Custom(ca) = origin_action {
-        for command in commands {
-            if command.name == ca {
-                action = command.action.to_owned();
-                // Override parameters with the ones from custom action
-                let cmd_params : TestParameters = (&command.parameters).into();
-                for (name, value) in cmd_params.inner() {
-                    parameters.insert(name, value.clone());
-                }
-                break
-            }
-        };
-        if action == TestActionType::Null {
-            return Err(ChaosError::Other(format!("Custom action {} not found", ca)))
-        }
-    }
-    parameters.replace_with_vars(state.db.get_variables());
+    let (action, parameters) = resolve_action(&origin_action, state, task)?;
     let res = match &action {
         TestActionType::Package(action) => installation::package_action(action, &parameters),
+        TestActionType::Service(ServiceActionType::CrashRecovery) => service::crash_recovery(&parameters).map(|recovery| {
+            task.data = recovery.into();
+        }),
         TestActionType::Service(action) => service::service_action(action, &parameters),
-        TestActionType::Metrics(action) => metrics::metric_action(action, &parameters),
+        TestActionType::Limits(action) => service::limits_action(action, &parameters),
+        TestActionType::Network(action) => network::network_action(action, &parameters),
+        TestActionType::Firewall(action) => firewall::firewall_action(action, &parameters),
+        TestActionType::Stress(action) => stress::stress_action(action, &parameters),
+        TestActionType::Filesystem(action) => filesystem::filesystem_action(action, &parameters),
+        TestActionType::Time(action) => time::time_action(action, &parameters).map(|skew| {
+            task.data.insert(TIME_SKEW, TestParameter::I64(skew));
+        }),
+        TestActionType::Metrics(MetricActionType::AssertNoLeak) => metrics::assert_no_leak(&parameters).map(|trend| {
+            task.data.insert(METRIC_RAM_TREND, (&trend).into());
+        }),
+        TestActionType::Metrics(action) => metrics::metric_action(action, &parameters).map(|stats| {
+            for (metric, stats) in stats.unwrap_or_default() {
+                task.data.insert(&format!("{}_{}", METRIC_STATS, metric), (&stats).into());
+            }
+        }),
         TestActionType::Log(action) => watchlog::watchlog_action(action, &parameters, state),
         TestActionType::Dns(action) => dns::dns_action(action, &parameters),
+        TestActionType::Snapshot(action) => snapshot::snapshot_action(action, &parameters),
+        TestActionType::Trace(action) => trace::trace_action(action, &parameters),
+        TestActionType::Audit(action) => audit::audit_action(action, &parameters),
+        TestActionType::Process(action) => process::process_action(action, &parameters).map(|pids| {
+            task.data.insert(AFFECTED_PIDS, TestParameter::Vec(pids.into_iter().map(|v| TestParameter::U64(v as u64)).collect()));
+        }),
         TestActionType::Http(_) => Ok(()),
         TestActionType::Artifact(action) => match action {
             ArtifactActionType::Download => download::download_file(&parameters),
@@ -81,6 +96,15 @@ pub fn execute_action(origin_action : TestActionType, state : &mut AgentState, t
             }
             Ok(())
         },
+        TestActionType::Soak => {
+            task.retries += 1;
+            // Return while the soak is running
+            match soak::soak_step(state, task, &parameters) {
+                Some(v) => v,
+                None => return Ok(())
+            }
+        },
+        TestActionType::Chaos(_) => Err(ChaosError::Other("Chaos phases are replaced by the server with the faults to inject".into())),
         TestActionType::Custom(action) => Err(chaos_core::err::ChaosError::Other(format!("Custom action {} not found", action))),
         
     };
@@ -91,4 +115,55 @@ pub fn execute_action(origin_action : TestActionType, state : &mut AgentState, t
         task.result = None;
     }
     Ok(())
-}
\ No newline at end of file
+}
+/// Action to run and its parameters: global ones, overridden by the custom action and by the task. Variables are replaced
+pub fn resolve_action(origin_action : &TestActionType, state : &AgentState, task : &AgentTaskInternal) -> ChaosResult<(TestActionType, TestParameters)> {
+    let global_parameters = state.db.get_global_parameters();
+    let commands = state.db.get_commands();
+    let mut parameters: TestParameters = global_parameters.into();
+    let mut action = origin_action.clone();
+    if let TestActionType::Custom(ca) = origin_action {
+        for command in commands {
+            if &command.name == ca {
+                action = command.action.to_owned();
+                // Override parameters with the ones from custom action
+                let cmd_params : TestParameters = (&command.parameters).into();
+                for (name, value) in cmd_params.inner() {
+                    parameters.insert(name, value.clone());
+                }
+                break
+            }
+        };
+        if action == TestActionType::Null {
+            return Err(ChaosError::Other(format!("Custom action {} not found", ca)))
+        }
+    }
+    // Task parameters override the global and custom action ones. Ex: generated upgrade scenes
+    for (name, value) in task.parameters.inner() {
+        parameters.insert(name, value.clone());
+    }
+    let mut variables = state.db.get_variables().clone();
+    for (name, value) in task.variables.inner() {
+        variables.insert(name, value.clone());
+    }
+    parameters.replace_with_vars(&variables);
+    Ok((action, parameters))
+}
+
+/// Actions that don't need the agent state nor report progress. Ex: background actions and soak checks
+pub fn run_stateless_action(action : &TestActionType, parameters : &TestParameters) -> ChaosResult<()> {
+    match action {
+        TestActionType::Package(action) => installation::package_action(action, parameters),
+        TestActionType::Service(ServiceActionType::CrashRecovery) => service::crash_recovery(parameters).map(|_| ()),
+        TestActionType::Service(action) => service::service_action(action, parameters),
+        TestActionType::Limits(action) => service::limits_action(action, parameters),
+        TestActionType::Network(action) => network::network_action(action, parameters),
+        TestActionType::Firewall(action) => firewall::firewall_action(action, parameters),
+        TestActionType::Stress(action) => stress::stress_action(action, parameters),
+        TestActionType::Filesystem(action) => filesystem::filesystem_action(action, parameters),
+        TestActionType::Time(action) => time::time_action(action, parameters).map(|_| ()),
+        TestActionType::Process(action) => process::process_action(action, parameters).map(|_| ()),
+        TestActionType::Dns(action) => dns::dns_action(action, parameters),
+        _ => Err(ChaosError::Other(format!("{} cannot run in background", <&str>::from(action))))
+    }
+}
@@ -0,0 +1,115 @@
+use std::{process::{Command, Stdio}, time::Duration};
+
+use chaos_core::{action::network::NetworkParameters, err::ChaosResult};
+
+use crate::common::{spawn_child_and_check_return_code, spawn_child_and_return_stdout};
+
+/// Band of the prio qdisc that receives the traffic to the target host. The default priomap only uses bands 1 to 3
+const TARGET_BAND : &str = "1:4";
+
+/// Faults on all the traffic: root netem qdisc. Faults to a host: root prio qdisc with netem in the band selected by an u32 filter
+pub fn apply_network_faults(parameters : &NetworkParameters) -> ChaosResult<()> {
+    remove_network_faults(&parameters.interface)?;
+    let interface = parameters.interface.as_str();
+    let netem = netem_options(parameters);
+    let target = match &parameters.target_host {
+        Some(v) => v,
+        None => {
+            let mut args = vec!["qdisc", "add", "dev", interface, "root", "handle", "1:", "netem"];
+            args.extend(netem.iter().map(|v| v.as_str()));
+            return tc(&args, "Cannot add netem qdisc")
+        }
+    };
+    tc(&["qdisc", "add", "dev", interface, "root", "handle", "1:", "prio", "bands", "4"], "Cannot add prio qdisc")?;
+    let mut args = vec!["qdisc", "add", "dev", interface, "parent", TARGET_BAND, "handle", "40:", "netem"];
+    args.extend(netem.iter().map(|v| v.as_str()));
+    let (protocol, selector) = if parameters.target_is_ipv6() { ("ipv6", "ip6") } else { ("ip", "ip") };
+    let res = tc(&args, "Cannot add netem qdisc")
+        .and_then(|_| tc(&["filter", "add", "dev", interface, "parent", "1:", "protocol", protocol, "prio", "1", "u32", "match", selector, "dst", target, "flowid", TARGET_BAND], "Cannot add filter for target host"));
+    if res.is_err() {
+        let _ = tc(&["qdisc", "del", "dev", interface, "root"], "Cannot remove root qdisc");
+    }
+    res
+}
+
+/// Removes the root qdisc only if it contains a netem qdisc, so it can be called repeatedly
+pub fn remove_network_faults(interface : &str) -> ChaosResult<()> {
+    let mut cmd = Command::new("tc");
+    cmd.arg("qdisc").arg("show").arg("dev").arg(interface).stdout(Stdio::piped());
+    let stdout = spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "Cannot list qdiscs using tc")?;
+    if !has_netem(&stdout) {
+        return Ok(())
+    }
+    tc(&["qdisc", "del", "dev", interface, "root"], "Cannot remove root qdisc")
+}
+
+fn tc(args : &[&str], err_msg : &str) -> ChaosResult<()> {
+    let mut cmd = Command::new("tc");
+    cmd.args(args);
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(10), err_msg)
+}
+
+fn has_netem(qdiscs : &str) -> bool {
+    qdiscs.lines().any(|line| line.trim_start().starts_with("qdisc netem"))
+}
+
+fn netem_options(parameters : &NetworkParameters) -> Vec<String> {
+    let mut options = Vec::with_capacity(8);
+    if let Some(delay) = parameters.delay {
+        options.push("delay".to_string());
+        options.push(format!("{}ms", delay.as_millis()));
+        if let Some(jitter) = parameters.jitter {
+            options.push(format!("{}ms", jitter.as_millis()));
+        }
+    }
+    if let Some(loss) = parameters.loss {
+        options.push("loss".to_string());
+        options.push(format!("{}%", loss));
+    }
+    if let Some(corrupt) = parameters.corrupt {
+        options.push("corrupt".to_string());
+        options.push(format!("{}%", corrupt));
+    }
+    if let Some(rate) = &parameters.rate {
+        options.push("rate".to_string());
+        options.push(rate.clone());
+    }
+    options
+}
+
+#[test]
+fn should_build_netem_options() {
+    let parameters = NetworkParameters {
+        interface : "eth0".into(),
+        delay : Some(Duration::from_millis(100)),
+        jitter : Some(Duration::from_millis(20)),
+        loss : Some(2.5),
+        rate : Some("1mbit".into()),
+        ..Default::default()
+    };
+    assert_eq!(vec!["delay", "100ms", "20ms", "loss", "2.5%", "rate", "1mbit"], netem_options(&parameters));
+    assert!(has_netem("qdisc prio 1: root refcnt 2 bands 4\nqdisc netem 40: parent 1:4 limit 1000 delay 100ms\n"));
+    assert!(!has_netem("qdisc noqueue 0: root refcnt 2\n"));
+}
+
+/// Requires root and the sch_netem kernel module: cargo test -- --ignored
+#[test]
+#[ignore]
+fn should_degrade_and_restore_loopback() {
+    let qdiscs = || {
+        let mut cmd = Command::new("tc");
+        cmd.arg("qdisc").arg("show").arg("dev").arg("lo").stdout(Stdio::piped());
+        spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "tc").unwrap()
+    };
+    let parameters = NetworkParameters {
+        interface : "lo".into(),
+        target_host : Some("127.0.0.2".into()),
+        delay : Some(Duration::from_millis(50)),
+        ..Default::default()
+    };
+    apply_network_faults(&parameters).unwrap();
+    assert!(has_netem(&qdiscs()));
+    remove_network_faults("lo").unwrap();
+    remove_network_faults("lo").unwrap();
+    assert!(!has_netem(&qdiscs()));
+}
@@ -0,0 +1,50 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use std::{cell::RefCell, collections::BTreeSet};
+
+use chaos_core::{action::{network::NetworkParameters, NetworkActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+thread_local! {
+    /// Interfaces with network faults applied by the agent
+    pub static DEGRADED_INTERFACES: RefCell<BTreeSet<String>> = const { RefCell::new(BTreeSet::new()) };
+}
+
+pub fn network_action(action : &NetworkActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        NetworkActionType::Degrade => degrade_network(parameters),
+        NetworkActionType::Restore => restore_network(parameters),
+    }
+}
+
+/// Replaces the faults of the interface with the configured ones
+pub fn degrade_network(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : NetworkParameters = parameters.try_into()?;
+    if !parameters.has_faults() {
+        return Err(ChaosError::Other(format!("No network fault configured for interface {}", parameters.interface)))
+    }
+    log::info!("Degrading network of interface {}", parameters.interface);
+    apply_network_faults(&parameters)?;
+    DEGRADED_INTERFACES.with_borrow_mut(|v| v.insert(parameters.interface));
+    Ok(())
+}
+
+/// Removes the faults of the configured interface and of every interface degraded by the agent. Nothing to do if there are no faults
+pub fn restore_network(parameters : &TestParameters) -> ChaosResult<()> {
+    let mut interfaces = DEGRADED_INTERFACES.with_borrow(|v| v.clone());
+    if let Ok(parameters) = NetworkParameters::try_from(parameters) {
+        interfaces.insert(parameters.interface);
+    }
+    for interface in interfaces {
+        log::info!("Restoring network of interface {}", interface);
+        remove_network_faults(&interface)?;
+        DEGRADED_INTERFACES.with_borrow_mut(|v| v.remove(&interface));
+    }
+    Ok(())
+}
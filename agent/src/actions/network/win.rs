@@ -0,0 +1,9 @@
+use chaos_core::{action::network::NetworkParameters, err::{ChaosError, ChaosResult}};
+
+pub fn apply_network_faults(_parameters : &NetworkParameters) -> ChaosResult<()> {
+    Err(ChaosError::Other("Network faults are not supported in Windows".into()))
+}
+
+pub fn remove_network_faults(_interface : &str) -> ChaosResult<()> {
+    Err(ChaosError::Other("Network faults are not supported in Windows".into()))
+}
@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use chaos_core::{action::process::ProcessParameters, err::{ChaosError, ChaosResult}};
+use nix::{sys::signal::{kill, Signal}, unistd::Pid};
+
+use crate::actions::metrics::{get_pid_of_process_by_name, get_pid_of_service};
+
+pub fn kill_process(parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    let signal = parse_signal(&parameters.signal)?;
+    signal_process(parameters, signal)
+}
+
+pub fn suspend_process(parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    signal_process(parameters, Signal::SIGSTOP)
+}
+
+pub fn resume_process(parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    signal_process(parameters, Signal::SIGCONT)
+}
+
+fn signal_process(parameters : &ProcessParameters, signal : Signal) -> ChaosResult<Vec<u32>> {
+    let pid = target_pid(parameters)?;
+    log::info!("Sending {} to process {}", signal, pid);
+    kill(Pid::from_raw(pid as i32), signal).map_err(|e| ChaosError::Other(format!("Cannot send {} to process {}: {}", signal, pid, e)))?;
+    Ok(vec![pid])
+}
+
+fn target_pid(parameters : &ProcessParameters) -> ChaosResult<u32> {
+    let pid = match (&parameters.executable, &parameters.service_name) {
+        (Some(executable), _) => get_pid_of_process_by_name(executable)?,
+        (None, Some(service)) => get_pid_of_service(service)?,
+        (None, None) => return Err(ChaosError::Other("No target process".into()))
+    };
+    // systemd reports MainPID=0 when the service is not running. kill(0) would signal the agent process group
+    if pid == 0 {
+        return Err(ChaosError::Other("Target process is not running".into()))
+    }
+    Ok(pid)
+}
+
+/// Accepts SIGTERM, TERM or the signal number
+pub fn parse_signal(signal : &str) -> ChaosResult<Signal> {
+    let signal = signal.trim().to_uppercase();
+    if let Ok(number) = signal.parse::<i32>() {
+        return Signal::try_from(number).map_err(|_| ChaosError::Other(format!("Invalid signal {}", signal)))
+    }
+    Signal::from_str(&signal)
+        .or_else(|_| Signal::from_str(&format!("SIG{}", signal)))
+        .map_err(|_| ChaosError::Other(format!("Invalid signal {}", signal)))
+}
+
+#[test]
+fn should_parse_signals() {
+    assert_eq!(Signal::SIGKILL, parse_signal("SIGKILL").unwrap());
+    assert_eq!(Signal::SIGKILL, parse_signal("KILL").unwrap());
+    assert_eq!(Signal::SIGTERM, parse_signal("term").unwrap());
+    assert_eq!(Signal::SIGHUP, parse_signal("1").unwrap());
+    assert!(parse_signal("SIGNOPE").is_err());
+}
@@ -0,0 +1,20 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use chaos_core::{action::{process::ProcessParameters, ProcessActionType}, err::ChaosResult, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+/// Returns the PIDs affected by the action
+pub fn process_action(action : &ProcessActionType, parameters: &TestParameters) -> ChaosResult<Vec<u32>> {
+    let parameters : ProcessParameters = parameters.try_into()?;
+    match action {
+        ProcessActionType::Kill => kill_process(&parameters),
+        ProcessActionType::Suspend => suspend_process(&parameters),
+        ProcessActionType::Resume => resume_process(&parameters),
+    }
+}
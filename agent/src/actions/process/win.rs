@@ -0,0 +1,13 @@
+use chaos_core::{action::process::ProcessParameters, err::{ChaosError, ChaosResult}};
+
+pub fn kill_process(_parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    Err(ChaosError::Other("Process actions are not supported in Windows".into()))
+}
+
+pub fn suspend_process(_parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    Err(ChaosError::Other("Process actions are not supported in Windows".into()))
+}
+
+pub fn resume_process(_parameters : &ProcessParameters) -> ChaosResult<Vec<u32>> {
+    Err(ChaosError::Other("Process actions are not supported in Windows".into()))
+}
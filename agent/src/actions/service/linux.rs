@@ -1,6 +1,11 @@
-use chaos_core::{action::service::ServiceCommand, err::ChaosResult, parameters::TestParameters};
+use std::{collections::BTreeMap, time::{Duration, Instant}};
 
-use crate::common::spawn_child_and_check_return_code;
+use chaos_core::{action::service::{ServiceCommand, ServiceLimits}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use nix::{sys::signal::{kill, Signal}, unistd::Pid};
+
+use crate::common::{spawn_child_and_check_return_code, spawn_child_and_return_stdout};
+
+use super::CrashRecovery;
 
 pub fn stop_service(parameters: &TestParameters) -> ChaosResult<()> {
     let parameters: ServiceCommand = parameters.try_into()?;
@@ -28,4 +33,140 @@ pub fn service_is_running(parameters: &TestParameters) -> ChaosResult<()> {
     let mut cmd = std::process::Command::new("systemctl");
     cmd.arg("is-active").arg("--quiet").arg(parameters.name);
     spawn_child_and_check_return_code(cmd, parameters.timeout, "Cannot test service using systemctl")
-}
\ No newline at end of file
+}
+
+/// Kills the main process of the service and waits until systemd starts a new one
+pub fn crash_recovery(parameters: &TestParameters) -> ChaosResult<CrashRecovery> {
+    let parameters: ServiceCommand = parameters.try_into()?;
+    let before = service_properties(&parameters.name)?;
+    let crashed_pid = property_u64(&before, "MainPID").unwrap_or_default() as u32;
+    if crashed_pid == 0 {
+        return Err(ChaosError::Other(format!("Service {} is not running", parameters.name)))
+    }
+    let restarts_before = property_u64(&before, "NRestarts");
+    log::info!("Crashing service {} with PID {}", parameters.name, crashed_pid);
+    kill(Pid::from_raw(crashed_pid as i32), Signal::SIGKILL).map_err(|e| ChaosError::Other(format!("Cannot kill process {} of service {}: {}", crashed_pid, parameters.name, e)))?;
+    let crashed = Instant::now();
+    while crashed.elapsed() < parameters.timeout {
+        std::thread::sleep(Duration::from_millis(100));
+        let properties = match service_properties(&parameters.name) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        let pid = property_u64(&properties, "MainPID").unwrap_or_default() as u32;
+        let active = properties.get("ActiveState").map(|v| v == "active").unwrap_or(false);
+        if pid != 0 && pid != crashed_pid && active {
+            let restarts = match (restarts_before, property_u64(&properties, "NRestarts")) {
+                (Some(before), Some(after)) => Some(after.saturating_sub(before)),
+                _ => None
+            };
+            return Ok(CrashRecovery {
+                crashed_pid,
+                recovered_pid : pid,
+                recovery_time : crashed.elapsed(),
+                restarts
+            })
+        }
+    }
+    Err(ChaosError::Other(format!("Service {} not recovered after {:?}", parameters.name, parameters.timeout)))
+}
+
+fn service_properties(name : &str) -> ChaosResult<BTreeMap<String, String>> {
+    let mut cmd = std::process::Command::new("systemctl");
+    cmd.arg("show").arg("--property").arg("MainPID,NRestarts,ActiveState").arg(name).stdout(std::process::Stdio::piped());
+    let stdout = spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "Cannot extract service properties using systemctl")?;
+    Ok(parse_systemctl_properties(&stdout))
+}
+
+fn property_u64(properties : &BTreeMap<String, String>, name : &str) -> Option<u64> {
+    properties.get(name)?.parse().ok()
+}
+
+/// Output of systemctl show: one Name=value per line
+fn parse_systemctl_properties(stdout : &str) -> BTreeMap<String, String> {
+    stdout.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Drop-in of the service with the limits. Takes precedence over the drop-ins of the package
+const LIMITS_DROP_IN : &str = "zz-chaosbench-limits.conf";
+
+/// Writes the drop-in, reloads systemd and restarts the service
+pub fn apply_limits(limits : &ServiceLimits) -> ChaosResult<()> {
+    let content = limits_drop_in(limits);
+    if content.lines().count() <= 1 {
+        return Err(ChaosError::Other("No limits configured for the service".into()))
+    }
+    let path = limits_drop_in_path(&limits.service.name);
+    log::info!("Applying limits to service {} using {}", limits.service.name, path.to_string_lossy());
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder).map_err(|e| ChaosError::Other(format!("Cannot create drop-in folder {}: {}", folder.to_string_lossy(), e)))?;
+    }
+    std::fs::write(&path, content).map_err(|e| ChaosError::Other(format!("Cannot write drop-in {}: {}", path.to_string_lossy(), e)))?;
+    reload_and_restart(&limits.service)
+}
+
+/// Removes the drop-in, reloads systemd and restarts the service. Nothing to do if the drop-in does not exist
+pub fn reset_limits(limits : &ServiceLimits) -> ChaosResult<()> {
+    let path = limits_drop_in_path(&limits.service.name);
+    if !path.exists() {
+        return Ok(())
+    }
+    log::info!("Removing limits of service {}", limits.service.name);
+    std::fs::remove_file(&path).map_err(|e| ChaosError::Other(format!("Cannot remove drop-in {}: {}", path.to_string_lossy(), e)))?;
+    reload_and_restart(&limits.service)
+}
+
+fn reload_and_restart(service : &ServiceCommand) -> ChaosResult<()> {
+    let mut cmd = std::process::Command::new("systemctl");
+    cmd.arg("daemon-reload");
+    spawn_child_and_check_return_code(cmd, service.timeout, "Cannot reload units using systemctl")?;
+    let mut cmd = std::process::Command::new("systemctl");
+    cmd.arg("restart").arg(&service.name);
+    spawn_child_and_check_return_code(cmd, service.timeout, "Cannot restart service using systemctl")
+}
+
+fn limits_drop_in_path(name : &str) -> std::path::PathBuf {
+    let unit = if name.contains('.') { name.to_string() } else { format!("{}.service", name) };
+    std::path::Path::new("/etc/systemd/system").join(format!("{}.d", unit)).join(LIMITS_DROP_IN)
+}
+
+fn limits_drop_in(limits : &ServiceLimits) -> String {
+    let mut content = String::from("[Service]\n");
+    if let Some(v) = &limits.memory_max {
+        content.push_str(&format!("MemoryMax={}\n", v));
+    }
+    if let Some(v) = &limits.cpu_quota {
+        content.push_str(&format!("CPUQuota={}\n", v));
+    }
+    if let Some(v) = limits.tasks_max {
+        content.push_str(&format!("TasksMax={}\n", v));
+    }
+    if let Some(v) = limits.nofile {
+        content.push_str(&format!("LimitNOFILE={}\n", v));
+    }
+    content
+}
+
+#[test]
+fn should_generate_limits_drop_in() {
+    let limits = ServiceLimits {
+        service : ServiceCommand { name : "superprogram".into(), timeout : Duration::from_secs(30) },
+        memory_max : Some("512M".into()),
+        cpu_quota : Some("50%".into()),
+        nofile : Some(256),
+        ..Default::default()
+    };
+    assert_eq!("[Service]\nMemoryMax=512M\nCPUQuota=50%\nLimitNOFILE=256\n", limits_drop_in(&limits));
+    assert_eq!(std::path::Path::new("/etc/systemd/system/superprogram.service.d/zz-chaosbench-limits.conf"), limits_drop_in_path("superprogram"));
+}
+
+#[test]
+fn should_parse_systemctl_properties() {
+    let properties = parse_systemctl_properties("MainPID=1234\nNRestarts=2\nActiveState=active\n");
+    assert_eq!(Some(1234), property_u64(&properties, "MainPID"));
+    assert_eq!(Some(2), property_u64(&properties, "NRestarts"));
+    assert_eq!("active", properties["ActiveState"]);
+}
@@ -1,6 +1,6 @@
 #[cfg(target_os="windows")]
 pub mod win;
-use chaos_core::{action::ServiceActionType, err::ChaosResult, parameters::TestParameters};
+use chaos_core::{action::{names::{AFFECTED_PIDS, RECOVERED_PID, RECOVERY_TIME, SERVICE_RESTARTS}, service::ServiceLimits, LimitsActionType, ServiceActionType}, err::ChaosResult, parameters::{TestParameter, TestParameters}};
 #[cfg(target_os="windows")]
 pub use win::*;
 
@@ -15,6 +15,37 @@ pub fn service_action(action : &ServiceActionType, parameters: &TestParameters)
         ServiceActionType::Restart => restart_service(parameters),
         ServiceActionType::Stop => stop_service(parameters),
         ServiceActionType::Start => start_service(parameters),
-        ServiceActionType::IsRunning => service_is_running(parameters)
+        ServiceActionType::IsRunning => service_is_running(parameters),
+        ServiceActionType::CrashRecovery => crash_recovery(parameters).map(|_| ())
+    }
+}
+
+pub fn limits_action(action : &LimitsActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    let limits : ServiceLimits = parameters.try_into()?;
+    match action {
+        LimitsActionType::Apply => apply_limits(&limits),
+        LimitsActionType::Reset => reset_limits(&limits),
+    }
+}
+
+/// Result of a crash recovery
+pub struct CrashRecovery {
+    pub crashed_pid : u32,
+    pub recovered_pid : u32,
+    pub recovery_time : std::time::Duration,
+    /// Restarts counted by the service manager, when available
+    pub restarts : Option<u64>
+}
+
+impl From<CrashRecovery> for TestParameters {
+    fn from(value: CrashRecovery) -> Self {
+        let mut data = TestParameters::new();
+        data.insert(AFFECTED_PIDS, TestParameter::Vec(vec![TestParameter::U64(value.crashed_pid as u64)]));
+        data.insert(RECOVERED_PID, TestParameter::U64(value.recovered_pid as u64));
+        data.insert(RECOVERY_TIME, TestParameter::U64(value.recovery_time.as_millis() as u64));
+        if let Some(restarts) = value.restarts {
+            data.insert(SERVICE_RESTARTS, TestParameter::U64(restarts));
+        }
+        data
     }
 }
\ No newline at end of file
@@ -1,6 +1,9 @@
 use std::time::{Instant, Duration};
 
 use chaos_core::{action::service::ServiceCommand, parameters::TestParameters, err::{ChaosResult, ChaosError}};
+use crate::actions::metrics::win::get_pid_of_service;
+
+use super::CrashRecovery;
 use windows::{Win32::{System::Services::{OpenServiceW, OpenSCManagerW, SC_MANAGER_CONNECT, SC_MANAGER_ENUMERATE_SERVICE, SERVICE_INTERROGATE, SERVICE_CONTROL_STOP, CloseServiceHandle, ControlService, SERVICE_STATUS, StartServiceW, SERVICE_START, QueryServiceStatus, SERVICE_STOPPED, SERVICE_START_PENDING}, Security::SC_HANDLE}, core::PCWSTR};
 
 pub fn stop_service(parameters: &TestParameters) -> ChaosResult<()> {
@@ -170,4 +173,39 @@ fn get_manager_handle() -> ChaosResult<SC_HANDLE> {
         Err(_) => return Err(ChaosError::Unknown)
     };
     Ok(handle)
-}
\ No newline at end of file
+}
+
+/// Kills the service process and waits until the Service Control Manager restarts it. Requires recovery actions configured in the service
+pub fn crash_recovery(parameters: &TestParameters) -> ChaosResult<CrashRecovery> {
+    let parameters: ServiceCommand = parameters.try_into()?;
+    let crashed_pid = get_pid_of_service(&parameters.name)?;
+    if crashed_pid == 0 {
+        return Err(ChaosError::Other(format!("Service {} is not running", parameters.name)))
+    }
+    log::info!("Crashing service {} with PID {}", parameters.name, crashed_pid);
+    let mut cmd = std::process::Command::new("taskkill");
+    cmd.arg("/F").arg("/PID").arg(crashed_pid.to_string());
+    crate::common::spawn_child_and_check_return_code(cmd, Duration::from_secs(10), "Cannot kill service process using taskkill")?;
+    let crashed = Instant::now();
+    while crashed.elapsed() < parameters.timeout {
+        std::thread::sleep(Duration::from_millis(100));
+        let pid = get_pid_of_service(&parameters.name).unwrap_or_default();
+        if pid != 0 && pid != crashed_pid {
+            return Ok(CrashRecovery {
+                crashed_pid,
+                recovered_pid : pid,
+                recovery_time : crashed.elapsed(),
+                restarts : None
+            })
+        }
+    }
+    Err(ChaosError::Other(format!("Service {} not recovered after {:?}", parameters.name, parameters.timeout)))
+}
+
+pub fn apply_limits(_limits : &chaos_core::action::service::ServiceLimits) -> ChaosResult<()> {
+    Err(ChaosError::Other("Service limits are not supported in Windows".into()))
+}
+
+pub fn reset_limits(_limits : &chaos_core::action::service::ServiceLimits) -> ChaosResult<()> {
+    Err(ChaosError::Other("Service limits are not supported in Windows".into()))
+}
@@ -0,0 +1,99 @@
+use std::{collections::BTreeSet, fs::Metadata, os::unix::fs::PermissionsExt, process::Command};
+
+use chaos_core::action::snapshot::SystemSnapshot;
+
+pub const DEFAULT_SNAPSHOT_ROOTS : [&str; 7] = ["/etc", "/opt", "/usr/local", "/usr/bin", "/usr/sbin", "/usr/lib/systemd/system", "/var/lib"];
+
+/// Package manager and system databases that change with every install
+pub const DEFAULT_SNAPSHOT_IGNORE : [&str; 6] = ["/var/lib/dpkg", "/var/lib/rpm", "/var/lib/apt", "/var/lib/systemd", "/var/lib/dnf", "/etc/ld.so.cache"];
+
+const CRON_LOCATIONS : [&str; 4] = ["/etc/crontab", "/etc/cron.d", "/var/spool/cron/crontabs", "/var/spool/cron"];
+
+pub fn file_mode(metadata : &Metadata) -> u32 {
+    metadata.permissions().mode()
+}
+
+pub fn fill_system_snapshot(snapshot : &mut SystemSnapshot) {
+    snapshot.packages = installed_packages();
+    snapshot.units = systemd_units();
+    snapshot.users = first_field_of_lines(&std::fs::read_to_string("/etc/passwd").unwrap_or_default());
+    snapshot.groups = first_field_of_lines(&std::fs::read_to_string("/etc/group").unwrap_or_default());
+    snapshot.cron = cron_entries();
+}
+
+fn command_stdout(command : &mut Command) -> Option<String> {
+    let output = command.output().ok()?;
+    if !output.status.success() {
+        return None
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn installed_packages() -> BTreeSet<String> {
+    let packages = command_stdout(Command::new("dpkg-query").arg("-W").arg("-f=${Package}\n"))
+        .or_else(|| command_stdout(Command::new("rpm").arg("-qa").arg("--queryformat").arg("%{NAME}\n")))
+        .unwrap_or_default();
+    packages.lines().map(|v| v.trim()).filter(|v| !v.is_empty()).map(|v| v.to_string()).collect()
+}
+
+fn systemd_units() -> BTreeSet<String> {
+    let units = command_stdout(Command::new("systemctl").arg("list-unit-files").arg("--no-legend").arg("--no-pager").arg("--plain")).unwrap_or_default();
+    units.lines().filter_map(|v| v.split_whitespace().next()).map(|v| v.to_string()).collect()
+}
+
+/// Users of /etc/passwd or groups of /etc/group
+fn first_field_of_lines(content : &str) -> BTreeSet<String> {
+    content.lines()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && !v.starts_with('#'))
+        .filter_map(|v| v.split(':').next())
+        .map(|v| v.to_string())
+        .collect()
+}
+
+fn cron_entries() -> BTreeSet<String> {
+    let mut entries = BTreeSet::new();
+    for location in CRON_LOCATIONS {
+        let metadata = match std::fs::metadata(location) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        if metadata.is_file() {
+            cron_file_entries(location, &mut entries);
+            continue
+        }
+        let dir = match std::fs::read_dir(location) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        for entry in dir.flatten() {
+            if entry.path().is_file() {
+                cron_file_entries(&entry.path().to_string_lossy(), &mut entries);
+            }
+        }
+    }
+    entries
+}
+
+fn cron_file_entries(location : &str, entries : &mut BTreeSet<String>) {
+    let content = std::fs::read_to_string(location).unwrap_or_default();
+    entries.extend(cron_lines(location, &content));
+}
+
+/// Active lines of a crontab prefixed with the file location
+fn cron_lines(location : &str, content : &str) -> Vec<String> {
+    content.lines()
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty() && !v.starts_with('#'))
+        .map(|v| format!("{}: {}", location, v))
+        .collect()
+}
+
+#[test]
+fn should_parse_users_and_cron_entries() {
+    let passwd = "root:x:0:0:root:/root:/bin/bash\n# comment\nchaos:x:999:999::/var/lib/chaos:/usr/sbin/nologin\n";
+    let users = first_field_of_lines(passwd);
+    assert_eq!(vec!["chaos", "root"], users.iter().map(|v| v.as_str()).collect::<Vec<&str>>());
+    let crontab = "# m h dom mon dow user command\nSHELL=/bin/sh\n\n*/5 * * * * root /opt/chaos/bin/check\n";
+    assert_eq!(vec!["/etc/cron.d/chaos: SHELL=/bin/sh", "/etc/cron.d/chaos: */5 * * * * root /opt/chaos/bin/check"], cron_lines("/etc/cron.d/chaos", crontab));
+}
@@ -0,0 +1,108 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use std::{collections::BTreeMap, path::Path};
+
+use chaos_core::{action::{snapshot::{SnapshotFile, SnapshotParameters, SystemSnapshot}, SnapshotActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+use crate::{actions::dns::read_etc_file, api::upload_data, common::{create_file_path_in_workspace, get_home}};
+
+pub fn snapshot_action(action : &SnapshotActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        SnapshotActionType::Take => take_snapshot(parameters),
+        SnapshotActionType::Compare => compare_snapshot(parameters),
+    }
+}
+
+pub fn take_snapshot(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : SnapshotParameters = parameters.try_into()?;
+    log::info!("Taking system snapshot {}", parameters.name);
+    let snapshot = system_snapshot(&parameters);
+    let content = serde_json::to_vec(&snapshot).map_err(|e| ChaosError::Other(format!("Cannot serialize snapshot {}: {}", parameters.name, e)))?;
+    std::fs::write(snapshot_location(&parameters.name), content)?;
+    Ok(())
+}
+
+/// Compares the system against a stored snapshot. The differences are uploaded as an artifact
+pub fn compare_snapshot(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : SnapshotParameters = parameters.try_into()?;
+    log::info!("Comparing system against snapshot {}", parameters.name);
+    let content = std::fs::read(snapshot_location(&parameters.name)).map_err(|e| ChaosError::Other(format!("Snapshot {} not found: {}", parameters.name, e)))?;
+    let before : SystemSnapshot = serde_json::from_slice(&content).map_err(|e| ChaosError::Other(format!("Invalid snapshot {}: {}", parameters.name, e)))?;
+    let after = system_snapshot(&parameters);
+    let diff = before.compare(&after);
+    if diff.is_empty() {
+        return Ok(())
+    }
+    let artifact_name = format!("snapshot-{}-diff.json", parameters.name);
+    let content = serde_json::to_vec_pretty(&diff).unwrap_or_default();
+    if let Err(e) = upload_data(&artifact_name, content) {
+        log::warn!("Cannot upload snapshot differences: {}", e);
+    }
+    Err(ChaosError::Other(format!("System differs from snapshot {}: {}. See {}", parameters.name, diff.summary(), artifact_name)))
+}
+
+fn snapshot_location(name : &str) -> std::path::PathBuf {
+    create_file_path_in_workspace(&format!("snapshot-{}.json", name))
+}
+
+fn system_snapshot(parameters : &SnapshotParameters) -> SystemSnapshot {
+    let roots : Vec<String> = if parameters.roots.is_empty() {
+        DEFAULT_SNAPSHOT_ROOTS.iter().map(|v| v.to_string()).collect()
+    } else {
+        parameters.roots.clone()
+    };
+    let mut ignore : Vec<String> = DEFAULT_SNAPSHOT_IGNORE.iter().map(|v| v.to_string()).collect();
+    ignore.extend(parameters.ignore.iter().cloned());
+    // The agent workspace changes with every task
+    ignore.push(get_home().to_string_lossy().to_string());
+    let mut files = BTreeMap::new();
+    for root in &roots {
+        walk_files(Path::new(root), &ignore, &mut files);
+    }
+    let mut snapshot = SystemSnapshot {
+        files,
+        ..Default::default()
+    };
+    snapshot.hosts = read_etc_file()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && !v.starts_with('#'))
+        .collect();
+    fill_system_snapshot(&mut snapshot);
+    snapshot
+}
+
+fn walk_files(path : &Path, ignore : &[String], files : &mut BTreeMap<String, SnapshotFile>) {
+    let location = path.to_string_lossy();
+    if ignore.iter().any(|v| location.starts_with(v.as_str())) {
+        return
+    }
+    // Do not follow symlinks
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    files.insert(location.to_string(), SnapshotFile {
+        size : metadata.len(),
+        mode : file_mode(&metadata),
+        is_dir : metadata.is_dir()
+    });
+    if !metadata.is_dir() {
+        return
+    }
+    let entries = match std::fs::read_dir(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        walk_files(&entry.path(), ignore, files);
+    }
+}
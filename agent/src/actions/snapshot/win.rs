@@ -0,0 +1,56 @@
+use std::{collections::BTreeSet, fs::Metadata, os::windows::fs::MetadataExt};
+
+use chaos_core::action::snapshot::SystemSnapshot;
+use windows::Win32::System::Registry::HKEY_LOCAL_MACHINE;
+
+use crate::reg::{self, RegValue};
+
+pub const DEFAULT_SNAPSHOT_ROOTS : [&str; 3] = [r"C:\Program Files", r"C:\Program Files (x86)", r"C:\ProgramData"];
+
+pub const DEFAULT_SNAPSHOT_IGNORE : [&str; 2] = [r"C:\ProgramData\Microsoft", r"C:\ProgramData\Package Cache"];
+
+const UNINSTALL_KEYS : [&str; 2] = [r"SOFTWARE\Microsoft\Windows\CurrentVersion\Uninstall", r"SOFTWARE\Wow6432Node\Microsoft\Windows\CurrentVersion\Uninstall"];
+
+/// File attributes: readonly, hidden, system...
+pub fn file_mode(metadata : &Metadata) -> u32 {
+    metadata.file_attributes()
+}
+
+/// Users, groups and cron entries are not recorded in Windows
+pub fn fill_system_snapshot(snapshot : &mut SystemSnapshot) {
+    let registry = reg::RegistryEditor::new();
+    snapshot.packages = installed_products(&registry);
+    snapshot.units = registry_subkeys(&registry, r"SYSTEM\CurrentControlSet\Services");
+}
+
+fn installed_products(registry : &reg::RegistryEditor) -> BTreeSet<String> {
+    let mut products = BTreeSet::new();
+    for uninstall_key in UNINSTALL_KEYS {
+        let uninstall_key = match registry.open_key(HKEY_LOCAL_MACHINE, uninstall_key) {
+            Ok(v) => v,
+            Err(_) => continue
+        };
+        for key in registry.enumerate_keys(uninstall_key).unwrap_or_default() {
+            let hkey = match registry.open_key(uninstall_key, &key) {
+                Ok(v) => v,
+                Err(_) => continue
+            };
+            if let Ok(RegValue::SZ(display_name)) = registry.read_value(hkey, "DisplayName") {
+                products.insert(display_name.trim().to_string());
+            }
+            registry.close_key(hkey);
+        }
+        registry.close_key(uninstall_key);
+    }
+    products
+}
+
+fn registry_subkeys(registry : &reg::RegistryEditor, location : &str) -> BTreeSet<String> {
+    let hkey = match registry.open_key(HKEY_LOCAL_MACHINE, location) {
+        Ok(v) => v,
+        Err(_) => return BTreeSet::new()
+    };
+    let keys = registry.enumerate_keys(hkey).unwrap_or_default().into_iter().collect();
+    registry.close_key(hkey);
+    keys
+}
@@ -0,0 +1,166 @@
+use std::{cell::RefCell, collections::BTreeMap, io::{Read, Seek, SeekFrom}, net::{TcpStream, ToSocketAddrs}, time::Duration};
+
+use chaos_core::{action::{names::SOAK_RESULTS, soak::{SoakCheck, SoakParameters}}, err::{ChaosError, ChaosResult}, parameters::{TestParameter, TestParameters}};
+
+use crate::{common::{now_milliseconds, AgentTaskInternal}, state::AgentState};
+
+use super::{resolve_action, run_stateless_action};
+
+const PORT_TIMEOUT : Duration = Duration::from_secs(5);
+
+thread_local! {
+    /// Task id and the position read in each log file
+    static LOG_OFFSETS : RefCell<(u32, BTreeMap<String, u64>)> = const { RefCell::new((u32::MAX, BTreeMap::new())) };
+}
+
+/// Runs a round of checks when it is due. None while the soak is running
+pub fn soak_step(state : &AgentState, task : &mut AgentTaskInternal, parameters : &TestParameters) -> Option<ChaosResult<()>> {
+    let soak : SoakParameters = match parameters.try_into() {
+        Ok(v) => v,
+        Err(e) => return Some(Err(e))
+    };
+    init_log_offsets(task.id, &soak);
+    let interval = soak.interval.as_millis() as i64;
+    let mut results = match task.data.get(SOAK_RESULTS) {
+        Some(TestParameter::Vec(v)) => v.clone(),
+        _ => Vec::new()
+    };
+    let now = now_milliseconds();
+    let last_round = results.last().and_then(result_timestamp);
+    if last_round.map(|v| now - v >= interval).unwrap_or(true) {
+        for check in &soak.checks {
+            let res = run_check(check, state, task);
+            if let Err(e) = &res {
+                log::warn!("Soak check {} failed: {}", check.name(), e);
+            }
+            results.push(check_result(now, &check.name(), res));
+        }
+        let violations : Vec<&TestParameter> = results.iter().filter(|v| !result_is_ok(v)).collect();
+        let error = (violations.len() as u64 >= soak.max_violations).then(|| {
+            ChaosError::Other(format!("Soak failed with {} violations. Last: {}", violations.len(), serde_json::to_string(violations[violations.len() - 1]).unwrap_or_default()))
+        });
+        task.data.insert(SOAK_RESULTS, TestParameter::Vec(results));
+        task.progress = true;
+        if let Some(error) = error {
+            return Some(Err(error))
+        }
+    }
+    let remaining = soak.duration.as_millis() as i64 - (now - task.start);
+    if remaining <= 0 {
+        return Some(Ok(()))
+    }
+    let next_round = last_round.filter(|v| now - v < interval).map(|v| v + interval - now).unwrap_or(interval);
+    std::thread::sleep(Duration::from_millis(next_round.min(remaining).max(100) as u64));
+    None
+}
+
+fn run_check(check : &SoakCheck, state : &AgentState, task : &AgentTaskInternal) -> ChaosResult<()> {
+    match check {
+        SoakCheck::Action(action) => {
+            let (action, parameters) = resolve_action(action, state, task)?;
+            run_stateless_action(&action, &parameters)
+        },
+        SoakCheck::Port { host, port } => check_port(host, *port),
+        SoakCheck::LogAbsent { file, pattern } => check_log_absent(file, pattern)
+    }
+}
+
+fn check_port(host : &str, port : u16) -> ChaosResult<()> {
+    let addresses = (host, port).to_socket_addrs().map_err(|e| ChaosError::Other(format!("Cannot resolve {}: {}", host, e)))?;
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(&address, PORT_TIMEOUT) {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = Some(e)
+        }
+    }
+    Err(ChaosError::Other(format!("Cannot connect to {}:{}: {}", host, port, last_error.map(|v| v.to_string()).unwrap_or_default())))
+}
+
+/// Only the complete lines written since the last check are read
+fn check_log_absent(file : &str, pattern : &str) -> ChaosResult<()> {
+    let mut offset = LOG_OFFSETS.with_borrow(|v| v.1.get(file).copied().unwrap_or_default());
+    let mut log = match std::fs::File::open(file) {
+        Ok(v) => v,
+        Err(_) => return Ok(())
+    };
+    let size = log.metadata().map(|v| v.len()).unwrap_or_default();
+    if size < offset {
+        // Rotated or truncated
+        offset = 0;
+    }
+    let mut content = Vec::with_capacity((size - offset) as usize);
+    log.seek(SeekFrom::Start(offset)).and_then(|_| log.read_to_end(&mut content)).map_err(|e| ChaosError::Other(format!("Cannot read {}: {}", file, e)))?;
+    let complete = content.iter().rposition(|v| *v == b'\n').map(|v| v + 1).unwrap_or(0);
+    LOG_OFFSETS.with_borrow_mut(|v| v.1.insert(file.to_string(), offset + complete as u64));
+    let content = String::from_utf8_lossy(&content[..complete]);
+    match content.lines().find(|line| line.contains(pattern)) {
+        Some(line) => Err(ChaosError::Other(format!("Found {:?} in {}: {}", pattern, file, line.trim()))),
+        None => Ok(())
+    }
+}
+
+/// Lines written before the soak started are not checked
+fn init_log_offsets(task_id : u32, soak : &SoakParameters) {
+    LOG_OFFSETS.with_borrow_mut(|(id, offsets)| {
+        if *id == task_id {
+            return
+        }
+        *id = task_id;
+        offsets.clear();
+        for check in &soak.checks {
+            if let SoakCheck::LogAbsent { file, .. } = check {
+                offsets.insert(file.clone(), std::fs::metadata(file).map(|v| v.len()).unwrap_or_default());
+            }
+        }
+    });
+}
+
+fn check_result(timestamp : i64, name : &str, res : ChaosResult<()>) -> TestParameter {
+    let mut result = BTreeMap::new();
+    result.insert("check".to_string(), TestParameter::Text(name.to_string()));
+    result.insert("timestamp".to_string(), TestParameter::I64(timestamp));
+    result.insert("result".to_string(), TestParameter::Text(match res {
+        Ok(_) => "Ok".to_string(),
+        Err(e) => e.to_string()
+    }));
+    TestParameter::Obj(result)
+}
+
+fn result_timestamp(result : &TestParameter) -> Option<i64> {
+    match result {
+        TestParameter::Obj(v) => match v.get("timestamp") {
+            Some(TestParameter::I64(v)) => Some(*v),
+            Some(TestParameter::U64(v)) => Some(*v as i64),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn result_is_ok(result : &TestParameter) -> bool {
+    match result {
+        TestParameter::Obj(v) => matches!(v.get("result"), Some(TestParameter::Text(v)) if v == "Ok"),
+        _ => false
+    }
+}
+
+#[test]
+fn should_check_only_new_log_lines() {
+    let file = std::env::temp_dir().join(format!("chaos-soak-{}.log", std::process::id()));
+    let file_name = file.to_string_lossy().to_string();
+    std::fs::write(&file, "panic before the soak\n").unwrap();
+    let soak = SoakParameters {
+        checks : vec![SoakCheck::LogAbsent { file : file_name.clone(), pattern : "panic".into() }],
+        ..Default::default()
+    };
+    init_log_offsets(1, &soak);
+    assert!(check_log_absent(&file_name, "panic").is_ok());
+    let mut log = std::fs::OpenOptions::new().append(true).open(&file).unwrap();
+    std::io::Write::write_all(&mut log, b"started\npan").unwrap();
+    assert!(check_log_absent(&file_name, "panic").is_ok());
+    std::io::Write::write_all(&mut log, b"ic in worker\n").unwrap();
+    let res = check_log_absent(&file_name, "panic");
+    let _ = std::fs::remove_file(&file);
+    assert_eq!(format!("Found \"panic\" in {}: panic in worker", file_name), res.unwrap_err().to_string());
+}
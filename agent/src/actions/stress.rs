@@ -0,0 +1,147 @@
+use std::{cell::RefCell, io::{Seek, SeekFrom, Write}, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle, time::{Duration, Instant}};
+
+use chaos_core::{action::{stress::StressParameters, StressActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+
+use crate::common::create_file_in_temp;
+
+const MEGABYTE : usize = 1024 * 1024;
+const PAGE_SIZE : usize = 4096;
+
+/// Stopper and load generator threads of a stress action
+type RunningStress = (Arc<AtomicBool>, Vec<JoinHandle<()>>);
+
+thread_local! {
+    pub static STRESS: RefCell<Vec<RunningStress>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn stress_action(action : &StressActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    if let StressActionType::Stop = action {
+        return stop_stress()
+    }
+    let parameters : StressParameters = parameters.try_into()?;
+    let stopper = Arc::new(AtomicBool::new(true));
+    let control = StressControl {
+        stopper : stopper.clone(),
+        deadline : parameters.duration.map(|v| Instant::now() + v)
+    };
+    let handles = match action {
+        StressActionType::Cpu => stress_cpu(&parameters, control),
+        StressActionType::Memory => stress_memory(&parameters, control)?,
+        StressActionType::Io => stress_io(&parameters, control)?,
+        StressActionType::Stop => Vec::new(),
+    };
+    STRESS.with_borrow_mut(|v| v.push((stopper, handles)));
+    Ok(())
+}
+
+/// Stops the load generators still running
+pub fn stop_stress() -> ChaosResult<()> {
+    let running = STRESS.with_borrow_mut(std::mem::take);
+    for (stopper, _) in &running {
+        stopper.store(false, Ordering::Relaxed);
+    }
+    for (_, handles) in running {
+        for handle in handles {
+            let _ = handle.join();
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+struct StressControl {
+    stopper : Arc<AtomicBool>,
+    deadline : Option<Instant>
+}
+
+impl StressControl {
+    fn running(&self) -> bool {
+        self.stopper.load(Ordering::Relaxed) && self.deadline.map(|v| Instant::now() < v).unwrap_or(true)
+    }
+}
+
+fn stress_cpu(parameters : &StressParameters, control : StressControl) -> Vec<JoinHandle<()>> {
+    let threads = parameters.threads.unwrap_or_else(|| std::thread::available_parallelism().map(|v| v.get()).unwrap_or(1));
+    log::info!("Starting CPU stress with {} threads", threads);
+    (0..threads).map(|_| {
+        let control = control.clone();
+        std::thread::spawn(move || {
+            let mut value = 0u64;
+            while control.running() {
+                for i in 0..100_000u64 {
+                    value = std::hint::black_box(value.wrapping_mul(6364136223846793005).wrapping_add(i));
+                }
+            }
+        })
+    }).collect()
+}
+
+fn stress_memory(parameters : &StressParameters, control : StressControl) -> ChaosResult<Vec<JoinHandle<()>>> {
+    if parameters.memory_mb == 0 {
+        return Err(ChaosError::Other("Stress::Memory requires the stress_memory_mb parameter".into()))
+    }
+    let size = parameters.memory_mb as usize * MEGABYTE;
+    let mut memory : Vec<u8> = Vec::new();
+    memory.try_reserve_exact(size).map_err(|e| ChaosError::Other(format!("Cannot allocate {} MB: {}", parameters.memory_mb, e)))?;
+    log::info!("Starting memory stress with {} MB", parameters.memory_mb);
+    Ok(vec![std::thread::spawn(move || {
+        // Writing a byte of each page makes the memory resident
+        memory.resize(size, 0);
+        for i in (0..size).step_by(PAGE_SIZE) {
+            memory[i] = 1;
+        }
+        std::hint::black_box(&memory);
+        while control.running() {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    })])
+}
+
+fn stress_io(parameters : &StressParameters, control : StressControl) -> ChaosResult<Vec<JoinHandle<()>>> {
+    let threads = parameters.threads.unwrap_or(1);
+    let size = parameters.io_size_mb as usize * MEGABYTE;
+    log::info!("Starting disk I/O stress with {} threads", threads);
+    let mut handles = Vec::with_capacity(threads);
+    for i in 0..threads {
+        let path = create_file_in_temp(&format!("stress-io-{}.bin", i));
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let mut file = std::fs::File::create(&path).map_err(|e| ChaosError::Other(format!("Cannot create stress file {}: {}", path.to_string_lossy(), e)))?;
+        let control = control.clone();
+        handles.push(std::thread::spawn(move || {
+            let chunk = vec![0xA5u8; MEGABYTE];
+            let mut written = 0;
+            while control.running() {
+                if written >= size {
+                    if file.seek(SeekFrom::Start(0)).is_err() {
+                        break
+                    }
+                    written = 0;
+                }
+                if let Err(e) = file.write_all(&chunk).and_then(|_| file.sync_data()) {
+                    log::warn!("Disk I/O stress stopped: {}", e);
+                    break
+                }
+                written += chunk.len();
+            }
+            drop(file);
+            let _ = std::fs::remove_file(&path);
+        }));
+    }
+    Ok(handles)
+}
+
+#[test]
+fn should_stop_stress() {
+    let mut parameters = TestParameters::new();
+    parameters.insert(chaos_core::action::names::STRESS_THREADS, chaos_core::parameters::TestParameter::U64(2));
+    parameters.insert(chaos_core::action::names::STRESS_MEMORY_MB, chaos_core::parameters::TestParameter::U64(4));
+    stress_action(&StressActionType::Cpu, &parameters).unwrap();
+    stress_action(&StressActionType::Memory, &parameters).unwrap();
+    assert_eq!(2, STRESS.with_borrow(|v| v.len()));
+    std::thread::sleep(Duration::from_millis(100));
+    stress_action(&StressActionType::Stop, &parameters).unwrap();
+    assert!(STRESS.with_borrow(|v| v.is_empty()));
+    stress_action(&StressActionType::Stop, &parameters).unwrap();
+}
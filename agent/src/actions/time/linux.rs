@@ -0,0 +1,40 @@
+use std::{process::{Command, Stdio}, time::Duration};
+
+use chaos_core::err::{ChaosError, ChaosResult};
+use nix::{sys::time::TimeSpec, time::{clock_gettime, clock_settime, ClockId}};
+
+use crate::common::{spawn_child_and_check_return_code, spawn_child_and_return_stdout};
+
+/// Moves CLOCK_REALTIME by the offset in milliseconds
+pub fn adjust_system_time(offset : i64) -> ChaosResult<()> {
+    let now = clock_gettime(ClockId::CLOCK_REALTIME).map_err(|e| ChaosError::Other(format!("Cannot read system clock: {}", e)))?;
+    let time = now + TimeSpec::from_duration(Duration::from_millis(offset.unsigned_abs())) * offset.signum() as i32;
+    clock_settime(ClockId::CLOCK_REALTIME, time).map_err(|e| ChaosError::Other(format!("Cannot set system clock: {}", e)))
+}
+
+/// Disables NTP synchronization. Returns true if it was enabled
+pub fn stop_time_sync() -> ChaosResult<bool> {
+    let mut cmd = Command::new("timedatectl");
+    cmd.arg("show").arg("--property").arg("NTP").arg("--value").stdout(Stdio::piped()).stderr(Stdio::null());
+    let enabled = match spawn_child_and_return_stdout(cmd, Duration::from_secs(10), "Cannot query NTP using timedatectl") {
+        Ok(v) => v.trim() == "yes",
+        Err(e) => {
+            log::warn!("{}", e);
+            return Ok(false)
+        }
+    };
+    if enabled {
+        set_ntp(false)?;
+    }
+    Ok(enabled)
+}
+
+pub fn start_time_sync() -> ChaosResult<()> {
+    set_ntp(true)
+}
+
+fn set_ntp(enabled : bool) -> ChaosResult<()> {
+    let mut cmd = Command::new("timedatectl");
+    cmd.arg("set-ntp").arg(if enabled { "true" } else { "false" });
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(30), "Cannot configure NTP using timedatectl")
+}
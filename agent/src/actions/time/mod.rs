@@ -0,0 +1,118 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use std::sync::atomic::Ordering;
+
+use chaos_core::{action::{time::TimeParameters, TimeActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+use serde::{Deserialize, Serialize};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+use crate::common::{create_file_path_in_workspace, CLOCK_SKEW};
+
+/// Workspace file with the state of the clock. Survives agent restarts
+const CLOCK_STATE_FILE : &str = "clock-state.json";
+const FAKETIME_FILE : &str = "faketime.rc";
+/// libfaketime offset that keeps the real time
+const FAKETIME_REAL_TIME : &str = "+0";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct ClockState {
+    /// Milliseconds the system clock was moved
+    skew : i64,
+    /// Time synchronization was running before the first shift
+    time_sync_stopped : bool,
+    /// Timestamp files with a frozen date
+    frozen_files : Vec<String>
+}
+
+/// Returns the skew of the system clock in milliseconds
+pub fn time_action(action : &TimeActionType, parameters: &TestParameters) -> ChaosResult<i64> {
+    let parameters : TimeParameters = parameters.try_into()?;
+    match action {
+        TimeActionType::Shift => shift_time(&parameters),
+        TimeActionType::Freeze => freeze_time(&parameters),
+        TimeActionType::Restore => restore_time(),
+    }
+}
+
+/// Applies the skew recorded before an agent restart to the timestamps
+pub fn load_clock_skew() {
+    CLOCK_SKEW.store(load_state().skew, Ordering::Relaxed);
+}
+
+fn shift_time(parameters : &TimeParameters) -> ChaosResult<i64> {
+    let offset = parameters.offset.ok_or_else(|| ChaosError::Other("Time::Shift requires the time_offset parameter".into()))?;
+    let mut state = load_state();
+    if state.skew == 0 && !state.time_sync_stopped {
+        // Time synchronization would undo the shift
+        state.time_sync_stopped = stop_time_sync()?;
+        // Saved before moving the clock so Time::Restore enables it again if the shift fails or the agent restarts
+        save_state(&state)?;
+    }
+    log::info!("Moving system clock by {} ms", offset);
+    adjust_system_time(offset)?;
+    state.skew += offset;
+    CLOCK_SKEW.store(state.skew, Ordering::Relaxed);
+    save_state(&state)?;
+    Ok(state.skew)
+}
+
+fn freeze_time(parameters : &TimeParameters) -> ChaosResult<i64> {
+    let date = parameters.freeze.as_ref().ok_or_else(|| ChaosError::Other("Time::Freeze requires the time_freeze parameter".into()))?;
+    let file = faketime_file(parameters);
+    log::info!("Freezing libfaketime clock at {} using {}", date, file);
+    std::fs::write(&file, date).map_err(|e| ChaosError::Other(format!("Cannot write libfaketime file {}: {}", file, e)))?;
+    let mut state = load_state();
+    if !state.frozen_files.contains(&file) {
+        state.frozen_files.push(file);
+    }
+    save_state(&state)?;
+    Ok(state.skew)
+}
+
+/// Moves the clock back by the recorded skew, so the time elapsed since the shift is kept
+fn restore_time() -> ChaosResult<i64> {
+    let mut state = load_state();
+    if state.skew != 0 {
+        log::info!("Moving system clock back by {} ms", state.skew);
+        adjust_system_time(-state.skew)?;
+        state.skew = 0;
+        CLOCK_SKEW.store(0, Ordering::Relaxed);
+        save_state(&state)?;
+    }
+    if state.time_sync_stopped {
+        start_time_sync()?;
+        state.time_sync_stopped = false;
+        save_state(&state)?;
+    }
+    while let Some(file) = state.frozen_files.pop() {
+        log::info!("Unfreezing libfaketime clock of {}", file);
+        std::fs::write(&file, FAKETIME_REAL_TIME).map_err(|e| ChaosError::Other(format!("Cannot write libfaketime file {}: {}", file, e)))?;
+        save_state(&state)?;
+    }
+    Ok(0)
+}
+
+fn faketime_file(parameters : &TimeParameters) -> String {
+    match &parameters.faketime_file {
+        Some(v) => v.clone(),
+        None => create_file_path_in_workspace(FAKETIME_FILE).to_string_lossy().to_string()
+    }
+}
+
+fn load_state() -> ClockState {
+    std::fs::read(create_file_path_in_workspace(CLOCK_STATE_FILE))
+        .ok()
+        .and_then(|v| serde_json::from_slice(&v).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state : &ClockState) -> ChaosResult<()> {
+    let content = serde_json::to_vec(state).map_err(|e| ChaosError::Other(format!("Cannot serialize clock state: {}", e)))?;
+    std::fs::write(create_file_path_in_workspace(CLOCK_STATE_FILE), content).map_err(|e| ChaosError::Other(format!("Cannot save clock state: {}", e)))
+}
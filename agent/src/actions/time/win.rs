@@ -0,0 +1,31 @@
+use std::{process::Command, time::Duration};
+
+use chaos_core::err::ChaosResult;
+
+use crate::common::spawn_child_and_check_return_code;
+
+/// Moves the system clock by the offset in milliseconds
+pub fn adjust_system_time(offset : i64) -> ChaosResult<()> {
+    let mut cmd = Command::new("powershell");
+    cmd.arg("-NoProfile").arg("-Command").arg(format!("Set-Date -Adjust ([TimeSpan]::FromMilliseconds({}))", offset));
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(30), "Cannot set system clock using Set-Date")
+}
+
+/// Stops the Windows Time service. Returns true if it was running
+pub fn stop_time_sync() -> ChaosResult<bool> {
+    let mut query = Command::new("w32tm");
+    query.arg("/query").arg("/status");
+    if spawn_child_and_check_return_code(query, Duration::from_secs(10), "Windows Time service not running").is_err() {
+        return Ok(false)
+    }
+    let mut cmd = Command::new("net");
+    cmd.arg("stop").arg("w32time");
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(30), "Cannot stop Windows Time service")?;
+    Ok(true)
+}
+
+pub fn start_time_sync() -> ChaosResult<()> {
+    let mut cmd = Command::new("net");
+    cmd.arg("start").arg("w32time");
+    spawn_child_and_check_return_code(cmd, Duration::from_secs(30), "Cannot start Windows Time service")
+}
@@ -0,0 +1,161 @@
+use std::{collections::BTreeMap, os::fd::AsRawFd, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle, time::Duration};
+
+use chaos_core::{action::trace::{TraceEvent, TraceOperation}, err::{ChaosError, ChaosResult}};
+use nix::{errno::Errno, sys::inotify::{AddWatchFlags, InitFlags, Inotify, WatchDescriptor}};
+
+use crate::common::{get_home, now_milliseconds};
+
+pub const DEFAULT_TRACE_ROOTS : [&str; 5] = ["/etc", "/opt", "/usr/local", "/var/lib", "/var/log"];
+
+fn watch_flags() -> AddWatchFlags {
+    AddWatchFlags::IN_CREATE
+        | AddWatchFlags::IN_MODIFY
+        | AddWatchFlags::IN_DELETE
+        | AddWatchFlags::IN_ATTRIB
+        | AddWatchFlags::IN_MOVED_FROM
+        | AddWatchFlags::IN_MOVED_TO
+        | AddWatchFlags::IN_ONLYDIR
+        | AddWatchFlags::IN_DONT_FOLLOW
+}
+
+pub fn spawn_trace(roots : Vec<String>, stopper : Arc<AtomicBool>) -> ChaosResult<JoinHandle<Vec<TraceEvent>>> {
+    let inotify = Inotify::init(InitFlags::IN_NONBLOCK | InitFlags::IN_CLOEXEC).map_err(|e| ChaosError::Other(format!("Cannot initialize inotify: {}", e)))?;
+    let mut watches = BTreeMap::new();
+    for root in &roots {
+        add_watches(inotify, Path::new(root), &mut watches, None);
+    }
+    if watches.is_empty() {
+        let _ = nix::unistd::close(inotify.as_raw_fd());
+        return Err(ChaosError::Other(format!("Cannot watch any of the trace roots {:?}", roots)))
+    }
+    Ok(std::thread::spawn(move || {
+        let mut events = Vec::with_capacity(1024);
+        loop {
+            let inotify_events = match inotify.read_events() {
+                Ok(v) => v,
+                Err(Errno::EAGAIN) => {
+                    if !stopper.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                    continue
+                },
+                Err(e) => {
+                    log::warn!("Cannot read inotify events: {}", e);
+                    break;
+                }
+            };
+            for event in inotify_events {
+                if event.mask.contains(AddWatchFlags::IN_Q_OVERFLOW) {
+                    log::warn!("Inotify queue overflow, some filesystem operations were lost");
+                    continue
+                }
+                let (parent, name) = match (watches.get(&event.wd), &event.name) {
+                    (Some(parent), Some(name)) => (parent, name),
+                    _ => continue
+                };
+                let path : PathBuf = Path::new(parent).join(name);
+                let operation = match operation_from_mask(event.mask) {
+                    Some(v) => v,
+                    None => continue
+                };
+                let timestamp = now_milliseconds();
+                if event.mask.contains(AddWatchFlags::IN_ISDIR) && matches!(operation, TraceOperation::Create | TraceOperation::MovedTo) {
+                    // The content created before the watch is registered is reported as created
+                    add_watches(inotify, &path, &mut watches, Some((&mut events, timestamp)));
+                }
+                push_event(&mut events, TraceEvent {
+                    path : path.to_string_lossy().to_string(),
+                    operation,
+                    timestamp
+                });
+            }
+        }
+        let _ = nix::unistd::close(inotify.as_raw_fd());
+        events
+    }))
+}
+
+/// Consecutive writes to the same file are reported once
+fn push_event(events : &mut Vec<TraceEvent>, event : TraceEvent) {
+    if let Some(last) = events.last() {
+        if last.operation == TraceOperation::Modify && event.operation == TraceOperation::Modify && last.path == event.path {
+            return
+        }
+    }
+    events.push(event);
+}
+
+fn add_watches(inotify : Inotify, path : &Path, watches : &mut BTreeMap<WatchDescriptor, String>, mut created : Option<(&mut Vec<TraceEvent>, i64)>) {
+    if path.starts_with(get_home()) {
+        return
+    }
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    if !metadata.is_dir() {
+        return
+    }
+    match inotify.add_watch(path, watch_flags()) {
+        Ok(wd) => {
+            watches.insert(wd, path.to_string_lossy().to_string());
+        },
+        Err(e) => {
+            log::warn!("Cannot watch {}: {}", path.to_string_lossy(), e);
+            return
+        }
+    }
+    let entries = match std::fs::read_dir(path) {
+        Ok(v) => v,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+        if let Some((events, timestamp)) = created.as_mut() {
+            push_event(events, TraceEvent {
+                path : entry_path.to_string_lossy().to_string(),
+                operation : TraceOperation::Create,
+                timestamp : *timestamp
+            });
+        }
+        add_watches(inotify, &entry_path, watches, created.as_mut().map(|(events, timestamp)| (&mut **events, *timestamp)));
+    }
+}
+
+fn operation_from_mask(mask : AddWatchFlags) -> Option<TraceOperation> {
+    Some(if mask.contains(AddWatchFlags::IN_CREATE) {
+        TraceOperation::Create
+    } else if mask.contains(AddWatchFlags::IN_DELETE) {
+        TraceOperation::Delete
+    } else if mask.contains(AddWatchFlags::IN_MODIFY) {
+        TraceOperation::Modify
+    } else if mask.contains(AddWatchFlags::IN_ATTRIB) {
+        TraceOperation::Attributes
+    } else if mask.contains(AddWatchFlags::IN_MOVED_FROM) {
+        TraceOperation::MovedFrom
+    } else if mask.contains(AddWatchFlags::IN_MOVED_TO) {
+        TraceOperation::MovedTo
+    } else {
+        return None
+    })
+}
+
+#[test]
+fn should_trace_filesystem_operations() {
+    let root = std::env::temp_dir().join(format!("chaos-trace-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    let stopper = Arc::new(AtomicBool::new(true));
+    let handle = spawn_trace(vec![root.to_string_lossy().to_string()], stopper.clone()).unwrap();
+    std::fs::create_dir_all(root.join("app")).unwrap();
+    std::fs::write(root.join("app").join("config.ini"), "a=1").unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    std::fs::remove_file(root.join("app").join("config.ini")).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    stopper.store(false, Ordering::Relaxed);
+    let events = handle.join().unwrap();
+    let _ = std::fs::remove_dir_all(&root);
+    let config = root.join("app").join("config.ini").to_string_lossy().to_string();
+    assert!(events.iter().any(|v| v.path == config && v.operation == TraceOperation::Create));
+    assert!(events.iter().any(|v| v.path == config && v.operation == TraceOperation::Delete));
+}
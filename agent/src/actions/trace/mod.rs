@@ -0,0 +1,68 @@
+#[cfg(target_os="windows")]
+pub mod win;
+use std::{cell::RefCell, collections::BTreeMap, sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle};
+
+use chaos_core::{action::{trace::{TraceArtifact, TraceEvent, TraceParameters}, TraceActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters};
+#[cfg(target_os="windows")]
+pub use win::*;
+
+#[cfg(target_os="linux")]
+pub mod linux;
+#[cfg(target_os="linux")]
+pub use linux::*;
+
+use crate::{api::upload_data, common::now_milliseconds};
+
+/// Stopper, trace metadata and the thread that returns the recorded operations
+type RunningTrace = (Arc<AtomicBool>, TraceArtifact, JoinHandle<Vec<TraceEvent>>);
+
+thread_local! {
+    pub static TRACES: RefCell<BTreeMap<String, RunningTrace>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub fn trace_action(action : &TraceActionType, parameters: &TestParameters) -> ChaosResult<()> {
+    match action {
+        TraceActionType::Start => start_trace(parameters),
+        TraceActionType::Stop => stop_trace(parameters),
+    }
+}
+
+pub fn start_trace(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : TraceParameters = parameters.try_into()?;
+    if TRACES.with_borrow(|v| v.contains_key(&parameters.name)) {
+        return Ok(())
+    }
+    let roots : Vec<String> = if parameters.roots.is_empty() {
+        DEFAULT_TRACE_ROOTS.iter().map(|v| v.to_string()).collect()
+    } else {
+        parameters.roots.clone()
+    };
+    log::info!("Starting filesystem trace {} on {:?}", parameters.name, roots);
+    let stopper = Arc::new(AtomicBool::new(true));
+    let handle = spawn_trace(roots.clone(), stopper.clone())?;
+    let artifact = TraceArtifact {
+        name : parameters.name.clone(),
+        roots,
+        start : now_milliseconds(),
+        ..Default::default()
+    };
+    TRACES.with_borrow_mut(|v| {
+        v.insert(parameters.name, (stopper, artifact, handle));
+    });
+    Ok(())
+}
+
+/// Stops the trace and uploads the recorded operations as an artifact
+pub fn stop_trace(parameters : &TestParameters) -> ChaosResult<()> {
+    let parameters : TraceParameters = parameters.try_into()?;
+    let (stopper, mut artifact, handle) = match TRACES.with_borrow_mut(|v| v.remove(&parameters.name)) {
+        Some(v) => v,
+        None => return Err(ChaosError::Other(format!("Trace {} not started", parameters.name)))
+    };
+    log::info!("Stopping filesystem trace {}", parameters.name);
+    stopper.store(false, Ordering::Relaxed);
+    artifact.events = handle.join().map_err(|_| ChaosError::Other(format!("Trace {} stopped unexpectedly", parameters.name)))?;
+    artifact.end = now_milliseconds();
+    let content = serde_json::to_vec_pretty(&artifact).map_err(|e| ChaosError::Other(format!("Cannot serialize trace {}: {}", parameters.name, e)))?;
+    upload_data(&format!("trace-{}.json", parameters.name), content)
+}
@@ -0,0 +1,9 @@
+use std::{sync::{atomic::AtomicBool, Arc}, thread::JoinHandle};
+
+use chaos_core::{action::trace::TraceEvent, err::{ChaosError, ChaosResult}};
+
+pub const DEFAULT_TRACE_ROOTS : [&str; 3] = [r"C:\Program Files", r"C:\Program Files (x86)", r"C:\ProgramData"];
+
+pub fn spawn_trace(_roots : Vec<String>, _stopper : Arc<AtomicBool>) -> ChaosResult<JoinHandle<Vec<TraceEvent>>> {
+    Err(ChaosError::Other("Filesystem trace is not supported in Windows".into()))
+}
@@ -1,6 +1,6 @@
-use std::{io::Read, path::PathBuf, process::Command, time::{Duration, SystemTime, UNIX_EPOCH}};
+use std::{io::Read, path::PathBuf, process::Command, sync::atomic::{AtomicI64, Ordering}, time::{Duration, SystemTime, UNIX_EPOCH}};
 
-use chaos_core::{action::TestActionType, err::{ChaosError, ChaosResult}, parameters::TestParameters, tasks::AgentTaskResult};
+use chaos_core::{action::TestActionType, err::{ChaosError, ChaosResult}, parameters::TestParameters, scenario::DuringAction, tasks::AgentTaskResult, variables::TestVariables};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
@@ -15,6 +15,17 @@ pub struct AgentTaskInternal {
     pub parameters : TestParameters,
     pub result : Option<Result<(), ChaosError>>,
     pub retries : u32,
+    #[serde(default)]
+    pub variables : TestVariables,
+    /// Data produced by the action
+    #[serde(default)]
+    pub data : TestParameters,
+    /// Actions repeated in background while the task runs
+    #[serde(default)]
+    pub during : Vec<DuringAction>,
+    /// The data changed and must be reported before the task completes
+    #[serde(default)]
+    pub progress : bool,
 }
 
 pub enum StopCommand {
@@ -22,7 +33,16 @@ pub enum StopCommand {
     Stop
 }
 
+/// Milliseconds the system clock was moved by Time::Shift
+pub static CLOCK_SKEW : AtomicI64 = AtomicI64::new(0);
+
+/// Real time, even if the system clock was moved by Time::Shift
 pub fn now_milliseconds() -> i64 {
+    system_milliseconds() - CLOCK_SKEW.load(Ordering::Relaxed)
+}
+
+/// Time of the system clock
+pub fn system_milliseconds() -> i64 {
     SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64
 }
 
@@ -72,6 +92,7 @@ impl From<AgentTaskInternal> for AgentTaskResult {
             result : v.result.unwrap_or_else(|| Ok(())),
             start : v.start,
             retries : v.retries,
+            data : v.data,
         }
     }
 }
@@ -79,7 +100,7 @@ impl From<AgentTaskInternal> for AgentTaskResult {
 impl From<&AgentTaskInternal> for AgentTaskResult {
     fn from(v: &AgentTaskInternal) -> Self {
         AgentTaskResult {
-            scene_id : v.id,
+            scene_id : v.scene_id,
             action : v.action.clone(),
             agent : v.agent.clone(),
             end : v.end.unwrap_or_default(),
@@ -88,7 +109,8 @@ impl From<&AgentTaskInternal> for AgentTaskResult {
             parameters : v.parameters.clone(),
             result : v.result.clone().unwrap_or_else(|| Ok(())),
             start : v.start,
-            retries : v.retries
+            retries : v.retries,
+            data : v.data.clone()
         }
     }
 }
@@ -1,4 +1,4 @@
-use chaos_core::{action::CustomAction, parameters::ScenarioParameters, tasks::AgentTask, variables::{ScenarioVariables, TestVariables}};
+use chaos_core::{action::CustomAction, parameters::{ScenarioParameters, TestParameters}, tasks::AgentTask, variables::{ScenarioVariables, TestVariables}};
 use serde::{Deserialize, Serialize};
 
 use crate::common::AgentTaskInternal;
@@ -39,7 +39,11 @@ impl Database {
             parameters : v.parameters,
             result : None,
             start : 0,
-            retries : v.retries
+            retries : v.retries,
+            variables : v.variables,
+            data : TestParameters::new(),
+            during : v.during,
+            progress : false
         });
     }
     pub fn set_global_parameters(&mut self, params : ScenarioParameters) {
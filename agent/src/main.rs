@@ -15,5 +15,6 @@ pub(crate) mod reg;
 
 fn main() {
     set_home();
+    actions::time::load_clock_skew();
     services::run();
 }
@@ -4,7 +4,7 @@ use chaos_core::{action::TestActionType, api::agent::{AgentRequest, AgentRespons
 use rustls::{ClientConfig, RootCertStore};
 use tungstenite::{handshake::client::generate_key, stream::MaybeTlsStream, Message, WebSocket};
 
-use crate::{actions::execute_action, api::SERVER_CERTIFICATE, common::{now_milliseconds, AgentTaskInternal, StopCommand}, logging::init_logging, state::{AgentState, SERVER_ADDRESS, SERVER_PORT}, sys_info::{get_hostname, get_system_uuid}};
+use crate::{actions::{during::{start_during_actions, stop_during_actions}, execute_action}, api::SERVER_CERTIFICATE, common::{now_milliseconds, AgentTaskInternal, StopCommand}, logging::init_logging, state::{AgentState, SERVER_ADDRESS, SERVER_PORT}, sys_info::{get_hostname, get_system_uuid}};
 
 type WsClient = WebSocket<MaybeTlsStream<TcpStream>>;
 
@@ -221,7 +221,8 @@ fn do_work(state : &mut AgentState, client : &mut WsClient) -> Result<(), tungst
     if task.start == 0 {
         task.start = now_milliseconds();
     }
-    match execute_action(task.action.clone(), state, &mut task) {
+    let res = start_during_actions(state, &task).and_then(|_| execute_action(task.action.clone(), state, &mut task));
+    match res {
         Ok(_) => {},
         Err(err) => {
             let tries = state.increase_task_try();
@@ -241,7 +242,12 @@ fn do_work(state : &mut AgentState, client : &mut WsClient) -> Result<(), tungst
         task.end = Some(now_milliseconds());
         task.result = Some(Err(ChaosError::Other(format!("Error executing task {}: Max retries reached", task.id))));
     }
+    if task.result.is_none() && task.progress {
+        task.progress = false;
+        client.send(agent_request_to_message(&AgentRequest::TaskProgress((&task).into())))?;
+    }
     if task.result.is_some() {
+        stop_during_actions(&mut task);
         let msg = format!("Sent completed task ({}) {:?}", task.id, task.action);
         client.send(agent_request_to_message(&AgentRequest::CompleteTask(task.into())))?;
         state.db.clean_current_task();
@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, get_vec_string_field, names::*};
+
+/// Mask of the permission bits, without setuid/setgid/sticky
+const PERMISSION_BITS: u32 = 0o777;
+const SETUID_BITS: u32 = 0o6000;
+const WORLD_WRITABLE: u32 = 0o002;
+
+/// Permission audit parameters:
+/// application_folders: Folders of the application to audit
+/// audit_allowed_modes: Octal modes allowed. Ex: ["755", "644"]. Any mode if not present
+/// audit_allowed_owners: User names or uids allowed to own the files. Any owner if not present
+/// audit_forbidden_setuid: Paths or file names that must not have the setuid/setgid bits. "*" (default) forbids all
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuditPermissionsParameters {
+    pub folders: Vec<String>,
+    pub policy: PermissionPolicy,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PermissionPolicy {
+    pub allowed_modes: Vec<u32>,
+    pub allowed_owners: Vec<String>,
+    pub forbidden_setuid: Vec<String>,
+}
+
+impl TryFrom<&TestParameters> for AuditPermissionsParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let folders = get_vec_string_field(params, APPLICATION_FOLDERS)?;
+        let mut allowed_modes = Vec::new();
+        for mode in get_vec_string_field(params, AUDIT_ALLOWED_MODES).unwrap_or_default() {
+            let parsed = u32::from_str_radix(mode.trim(), 8).map_err(|_| ChaosError::Other(format!("Invalid octal mode {}", mode)))?;
+            allowed_modes.push(parsed);
+        }
+        let allowed_owners = get_vec_string_field(params, AUDIT_ALLOWED_OWNERS).unwrap_or_default();
+        let forbidden_setuid = get_vec_string_field(params, AUDIT_FORBIDDEN_SETUID).unwrap_or_else(|_| vec!["*".to_string()]);
+        Ok(Self {
+            folders,
+            policy: PermissionPolicy {
+                allowed_modes,
+                allowed_owners,
+                forbidden_setuid,
+            },
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for AuditPermissionsParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+/// Listening ports audit parameters:
+/// service_name: Service of the application. Its main process and children are audited
+/// audit_process_name: Executable path of the application process, used instead of the service
+/// audit_allowed_ports: Ports the application can listen on. Ex: ["tcp:8443", "udp:5353", "9090"]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AuditListeningPortsParameters {
+    pub service_name: Option<String>,
+    pub process_name: Option<String>,
+    pub allowed_ports: Vec<AllowedPort>,
+}
+
+/// Port allowed for a protocol (tcp/udp) or for all of them
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowedPort {
+    pub protocol: Option<String>,
+    pub port: u16,
+}
+
+impl TryFrom<&str> for AllowedPort {
+    type Error = ChaosError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim().to_lowercase();
+        let (protocol, port) = match value.split_once(':') {
+            Some((protocol, port)) => (Some(protocol.to_string()), port),
+            None => (None, value.as_str()),
+        };
+        if let Some(protocol) = &protocol {
+            if protocol != "tcp" && protocol != "udp" {
+                return Err(ChaosError::Other(format!("Invalid protocol {}, expected tcp or udp", protocol)));
+            }
+        }
+        let port = port.parse::<u16>().map_err(|_| ChaosError::Other(format!("Invalid port {}", port)))?;
+        Ok(Self { protocol, port })
+    }
+}
+
+impl AllowedPort {
+    /// protocol of the socket: tcp, tcp6, udp or udp6
+    pub fn allows(&self, protocol: &str, port: u16) -> bool {
+        self.port == port && self.protocol.as_ref().map(|v| protocol.starts_with(v.as_str())).unwrap_or(true)
+    }
+}
+
+impl TryFrom<&TestParameters> for AuditListeningPortsParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let service_name = get_string_field(params, APP_SERVICE_NAME).ok();
+        let process_name = get_string_field(params, AUDIT_PROCESS_NAME).ok();
+        if service_name.is_none() && process_name.is_none() {
+            return Err(ChaosError::Other(format!("Parameter {:?} or {:?} not found", APP_SERVICE_NAME, AUDIT_PROCESS_NAME)));
+        }
+        let mut allowed_ports = Vec::new();
+        for port in get_vec_string_field(params, AUDIT_ALLOWED_PORTS).unwrap_or_default() {
+            allowed_ports.push(port.as_str().try_into()?);
+        }
+        Ok(Self {
+            service_name,
+            process_name,
+            allowed_ports,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for AuditListeningPortsParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+impl PermissionPolicy {
+    /// Reasons why a file breaks the policy. owner contains the user name or the uid if it has no name
+    pub fn check(&self, path: &str, mode: u32, is_symlink: bool, owner: &str, uid: u32) -> Vec<String> {
+        let mut reasons = Vec::new();
+        // Symlinks are always 777
+        if !is_symlink && mode & WORLD_WRITABLE != 0 {
+            reasons.push(format!("world-writable ({:o})", mode & PERMISSION_BITS));
+        }
+        if mode & SETUID_BITS != 0 && self.setuid_forbidden(path) {
+            reasons.push(format!("setuid/setgid ({:o})", mode & 0o7777));
+        }
+        if !is_symlink && !self.allowed_modes.is_empty() && !self.allowed_modes.contains(&(mode & PERMISSION_BITS)) {
+            reasons.push(format!("mode {:o} not allowed", mode & PERMISSION_BITS));
+        }
+        if !self.allowed_owners.is_empty() && !self.allowed_owners.iter().any(|v| v == owner || *v == uid.to_string()) {
+            reasons.push(format!("owned by {}", owner));
+        }
+        reasons
+    }
+
+    fn setuid_forbidden(&self, path: &str) -> bool {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        self.forbidden_setuid.iter().any(|v| v == "*" || v == path || v == file_name)
+    }
+}
+
+#[test]
+fn should_apply_permission_policy() {
+    let policy = PermissionPolicy {
+        allowed_modes: vec![0o755, 0o644],
+        allowed_owners: vec!["root".into()],
+        forbidden_setuid: vec!["*".into()],
+    };
+    assert!(policy.check("/opt/app/bin/app", 0o100755, false, "root", 0).is_empty());
+    assert!(policy.check("/opt/app/current", 0o120777, true, "root", 0).is_empty());
+    assert_eq!(vec!["world-writable (666)", "mode 666 not allowed"], policy.check("/opt/app/app.log", 0o100666, false, "root", 0));
+    assert_eq!(vec!["setuid/setgid (4755)"], policy.check("/opt/app/bin/helper", 0o104755, false, "root", 0));
+    assert_eq!(vec!["owned by app"], policy.check("/opt/app/app.conf", 0o100644, false, "app", 999));
+
+    let policy = PermissionPolicy {
+        forbidden_setuid: vec!["dangerous".into()],
+        ..Default::default()
+    };
+    assert!(policy.check("/opt/app/bin/helper", 0o104755, false, "root", 0).is_empty());
+    assert_eq!(1, policy.check("/opt/app/bin/dangerous", 0o104755, false, "root", 0).len());
+}
+
+#[test]
+fn should_parse_allowed_ports() {
+    let port: AllowedPort = "tcp:8443".try_into().unwrap();
+    assert!(port.allows("tcp", 8443));
+    assert!(port.allows("tcp6", 8443));
+    assert!(!port.allows("udp", 8443));
+    let port: AllowedPort = "9090".try_into().unwrap();
+    assert!(port.allows("udp6", 9090));
+    assert!(AllowedPort::try_from("sctp:80").is_err());
+    assert!(AllowedPort::try_from("tcp:http").is_err());
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, get_u64_field, names::*};
+
+/// Filesystem fault parameters:
+/// filesystem_path: Folder of the application. Its volume is filled by Filesystem::Fill, or a tmpfs or a read-only bind mount is placed over it
+/// filesystem_free_bytes: Bytes left free by Filesystem::Fill. 0 by default
+/// filesystem_tmpfs_size: Size of the tmpfs mounted by Filesystem::Tmpfs. Ex: 512k, 16m. 16m by default
+/// filesystem_fill_for: root (default) also fills the blocks reserved for root, so services running as root see the volume full.
+/// user only fills the space available to unprivileged users and leaves the reserved blocks free
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FilesystemParameters {
+    pub path: String,
+    pub free_bytes: u64,
+    pub tmpfs_size: String,
+    pub fill_reserved: bool,
+}
+
+impl TryFrom<&TestParameters> for FilesystemParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let path = get_string_field(params, FILESYSTEM_PATH)?;
+        let free_bytes = get_u64_field(params, FILESYSTEM_FREE_BYTES).unwrap_or_default();
+        let tmpfs_size = get_string_field(params, FILESYSTEM_TMPFS_SIZE).unwrap_or_else(|_| "16m".to_string()).to_lowercase();
+        let digits = tmpfs_size.trim_end_matches(['k', 'm', 'g']);
+        if digits.is_empty() || !digits.chars().all(|v| v.is_ascii_digit()) {
+            return Err(ChaosError::Other(format!("Invalid tmpfs size {}. Ex: 512k, 16m", tmpfs_size)));
+        }
+        let fill_reserved = match get_string_field(params, FILESYSTEM_FILL_FOR).unwrap_or_else(|_| "root".to_string()).to_lowercase().as_str() {
+            "root" => true,
+            "user" => false,
+            v => return Err(ChaosError::Other(format!("Invalid filesystem fill user {}, expected root or user", v))),
+        };
+        Ok(Self {
+            path,
+            free_bytes,
+            tmpfs_size,
+            fill_reserved,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for FilesystemParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[test]
+fn should_parse_filesystem_parameters() {
+    use crate::parameters::TestParameter;
+    let mut params = TestParameters::new();
+    params.insert(FILESYSTEM_PATH, TestParameter::Text("/var/lib/app".into()));
+    let filesystem: FilesystemParameters = (&params).try_into().unwrap();
+    assert_eq!(0, filesystem.free_bytes);
+    assert_eq!("16m", filesystem.tmpfs_size);
+    assert!(filesystem.fill_reserved);
+    params.insert(FILESYSTEM_FILL_FOR, TestParameter::Text("User".into()));
+    assert!(!FilesystemParameters::try_from(&params).unwrap().fill_reserved);
+    params.insert(FILESYSTEM_FILL_FOR, TestParameter::Text("nobody".into()));
+    assert!(FilesystemParameters::try_from(&params).is_err());
+    params.insert(FILESYSTEM_FILL_FOR, TestParameter::Text("root".into()));
+    params.insert(FILESYSTEM_TMPFS_SIZE, TestParameter::Text("m".into()));
+    assert!(FilesystemParameters::try_from(&params).is_err());
+    params.insert(FILESYSTEM_TMPFS_SIZE, TestParameter::Text("1,5g".into()));
+    assert!(FilesystemParameters::try_from(&params).is_err());
+}
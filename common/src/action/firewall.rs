@@ -0,0 +1,103 @@
+use std::{net::IpAddr, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, get_u64_field, names::*};
+
+/// Prefix of the comment that tags the firewall rules created by the agent
+pub const FIREWALL_MARK_PREFIX: &str = "ChaosBench-";
+
+/// Firewall parameters:
+/// firewall_host: Blocked IP address or network. Ex: 10.0.0.5, 10.0.0.0/24. Any host if not present
+/// firewall_port: Blocked destination port. Any port if not present
+/// firewall_protocol: tcp (default) or udp. Only used with firewall_port
+/// firewall_action: drop (default) silently discards the packets, reject answers with an error
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FirewallParameters {
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub protocol: String,
+    pub reject: bool,
+}
+
+impl FirewallParameters {
+    /// Comment that identifies the rules of these parameters. Same idea as the marks of the hosts file entries
+    pub fn mark(&self) -> String {
+        format!(
+            "{}{}-{}-{}",
+            FIREWALL_MARK_PREFIX,
+            self.host.as_deref().unwrap_or("any"),
+            self.port.map(|v| v.to_string()).unwrap_or_else(|| "any".to_string()),
+            self.protocol
+        )
+    }
+
+    /// Blocked host is an IPv6 address or network
+    pub fn is_ipv6(&self) -> bool {
+        self.host.as_ref().map(|v| v.contains(':')).unwrap_or(false)
+    }
+}
+
+impl TryFrom<&TestParameters> for FirewallParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let host = get_string_field(params, FIREWALL_HOST).ok();
+        let port = get_u64_field(params, FIREWALL_PORT).ok();
+        if host.is_none() && port.is_none() {
+            return Err(ChaosError::Other(format!("Parameter {:?} or {:?} not found", FIREWALL_HOST, FIREWALL_PORT)));
+        }
+        if let Some(host) = &host {
+            let (address, prefix) = match host.split_once('/') {
+                Some((address, prefix)) => (address, Some(prefix)),
+                None => (host.as_str(), None),
+            };
+            if IpAddr::from_str(address).is_err() || prefix.map(|v| v.parse::<u8>().is_err()).unwrap_or(false) {
+                return Err(ChaosError::Other(format!("Invalid firewall host {}, expected an IP address or network", host)));
+            }
+        }
+        let port = match port {
+            Some(v) => Some(u16::try_from(v).map_err(|_| ChaosError::Other(format!("Invalid firewall port {}", v)))?),
+            None => None,
+        };
+        let protocol = get_string_field(params, FIREWALL_PROTOCOL).unwrap_or_else(|_| "tcp".to_string()).to_lowercase();
+        if protocol != "tcp" && protocol != "udp" {
+            return Err(ChaosError::Other(format!("Invalid protocol {}, expected tcp or udp", protocol)));
+        }
+        let reject = match get_string_field(params, FIREWALL_ACTION).unwrap_or_else(|_| "drop".to_string()).to_lowercase().as_str() {
+            "drop" => false,
+            "reject" => true,
+            v => return Err(ChaosError::Other(format!("Invalid firewall action {}, expected drop or reject", v))),
+        };
+        Ok(Self {
+            host,
+            port,
+            protocol,
+            reject,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for FirewallParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[test]
+fn should_parse_firewall_parameters() {
+    use crate::parameters::TestParameter;
+    let mut params = TestParameters::new();
+    params.insert(FIREWALL_HOST, TestParameter::Text("10.0.0.5".into()));
+    params.insert(FIREWALL_PORT, TestParameter::U64(5432));
+    let firewall: FirewallParameters = (&params).try_into().unwrap();
+    assert_eq!("ChaosBench-10.0.0.5-5432-tcp", firewall.mark());
+    assert!(!firewall.reject);
+
+    params.insert(FIREWALL_ACTION, TestParameter::Text("Reject".into()));
+    params.insert(FIREWALL_PORT, TestParameter::U64(70000));
+    assert!(FirewallParameters::try_from(&params).is_err());
+    assert!(FirewallParameters::try_from(TestParameters::new()).is_err());
+}
@@ -0,0 +1,155 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Maintainer scripts of a Debian package
+pub const MAINTAINER_SCRIPTS: [&str; 4] = ["preinst", "postinst", "prerm", "postrm"];
+
+/// File installed by a package
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageFile {
+    pub path: String,
+    /// Symbolic mode. Ex: -rwxr-xr-x
+    pub mode: String,
+    pub owner: String,
+    pub size: u64,
+}
+
+/// Result of the static checks of a package
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PackageInspection {
+    pub installer: String,
+    pub control: BTreeMap<String, String>,
+    /// Maintainer script name and its content
+    pub scripts: BTreeMap<String, String>,
+    pub conffiles: Vec<String>,
+    pub files: Vec<PackageFile>,
+    pub problems: Vec<String>,
+}
+
+impl PackageInspection {
+    /// Flags common packaging problems
+    pub fn check(&mut self) {
+        let mut problems = Vec::new();
+        if !self.control.contains_key("Depends") && !self.control.contains_key("Pre-Depends") {
+            problems.push("Missing Depends field in control file".to_string());
+        }
+        for (name, content) in &self.scripts {
+            if is_shell_script(content) && !exits_on_error(content) {
+                problems.push(format!("Maintainer script {} without set -e", name));
+            }
+        }
+        for conffile in &self.conffiles {
+            if !conffile.starts_with("/etc/") {
+                problems.push(format!("Conffile {} outside /etc", conffile));
+            }
+        }
+        for file in &self.files {
+            if !file.mode.starts_with('d') && normalize_path(&file.path).starts_with("/usr/local/") {
+                problems.push(format!("File {} under /usr/local", normalize_path(&file.path)));
+            }
+        }
+        self.problems = problems;
+    }
+}
+
+/// Fields of a control file. Continuation lines are appended to the previous field
+pub fn parse_control_file(content: &str) -> BTreeMap<String, String> {
+    let mut fields: BTreeMap<String, String> = BTreeMap::new();
+    let mut last_field: Option<String> = None;
+    for line in content.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(value) = last_field.as_ref().and_then(|v| fields.get_mut(v)) {
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            fields.insert(name.trim().to_string(), value.trim().to_string());
+            last_field = Some(name.trim().to_string());
+        }
+    }
+    fields
+}
+
+/// Lines of dpkg-deb -c: -rwxr-xr-x root/root     12345 2024-01-01 12:00 ./usr/bin/app
+pub fn parse_package_contents(content: &str) -> Vec<PackageFile> {
+    let mut files = Vec::new();
+    for line in content.lines() {
+        let mut columns = line.split_whitespace();
+        let (mode, owner, size) = match (columns.next(), columns.next(), columns.next()) {
+            (Some(mode), Some(owner), Some(size)) => (mode, owner, size),
+            _ => continue,
+        };
+        // Date and time
+        let path: Vec<&str> = columns.skip(2).collect();
+        if path.is_empty() {
+            continue;
+        }
+        // Symlinks: ./usr/bin/app -> /opt/app/bin/app
+        let path = path.join(" ");
+        let path = path.split(" -> ").next().unwrap_or_default().to_string();
+        files.push(PackageFile {
+            path,
+            mode: mode.to_string(),
+            owner: owner.to_string(),
+            size: size.parse().unwrap_or_default(),
+        });
+    }
+    files
+}
+
+fn normalize_path(path: &str) -> String {
+    format!("/{}", path.trim_start_matches('.').trim_start_matches('/'))
+}
+
+fn is_shell_script(content: &str) -> bool {
+    let shebang = content.lines().next().unwrap_or_default();
+    shebang.starts_with("#!") && (shebang.ends_with("sh") || shebang.contains("sh "))
+}
+
+/// set -e in the script or -e in the shebang
+fn exits_on_error(content: &str) -> bool {
+    let mut lines = content.lines();
+    let shebang = lines.next().unwrap_or_default();
+    if shebang.split_whitespace().skip(1).any(|v| v.starts_with('-') && v.contains('e')) {
+        return true;
+    }
+    lines.any(|line| {
+        let line = line.trim();
+        match line.strip_prefix("set ") {
+            Some(flags) => flags.split_whitespace().any(|v| v.starts_with('-') && v.contains('e')),
+            None => false,
+        }
+    })
+}
+
+#[test]
+fn should_flag_packaging_problems() {
+    let control = "Package: chaos-bench\nVersion: 0.1.0\nDescription: ChaosBench\n extended description\n";
+    let contents = r#"drwxr-xr-x root/root         0 2024-01-01 12:00 ./
+drwxr-xr-x root/root         0 2024-01-01 12:00 ./usr/local/
+-rwxr-xr-x root/root     12345 2024-01-01 12:00 ./usr/local/bin/chaos
+-rw-r--r-- root/root        10 2024-01-01 12:00 ./etc/chaos/chaos.conf
+lrwxrwxrwx root/root         0 2024-01-01 12:00 ./usr/bin/chaos -> /usr/local/bin/chaos
+"#;
+    let mut inspection = PackageInspection {
+        control: parse_control_file(control),
+        files: parse_package_contents(contents),
+        conffiles: vec!["/etc/chaos/chaos.conf".into(), "/opt/chaos/chaos.ini".into()],
+        ..Default::default()
+    };
+    inspection.scripts.insert("postinst".into(), "#!/bin/sh\nsystemctl enable chaos\n".into());
+    inspection.scripts.insert("prerm".into(), "#!/bin/sh\nset -e\nsystemctl stop chaos\n".into());
+    inspection.scripts.insert("postrm".into(), "#!/bin/sh -e\nrm -rf /var/lib/chaos\n".into());
+    inspection.check();
+    assert_eq!("ChaosBench\nextended description", inspection.control["Description"]);
+    assert_eq!("./usr/bin/chaos", inspection.files[4].path);
+    assert_eq!(vec![
+        "Missing Depends field in control file",
+        "Maintainer script postinst without set -e",
+        "Conffile /opt/chaos/chaos.ini outside /etc",
+        "File /usr/local/bin/chaos under /usr/local",
+    ], inspection.problems);
+}
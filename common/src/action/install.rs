@@ -4,10 +4,31 @@ use serde::{Deserialize, Serialize};
 
 use crate::{parameters::{TestParameter, TestParameters}, err::{ChaosError, ChaosResult}};
 
-use super::{get_string_field, get_timeout_field, names::*};
+use super::{get_duration_field, get_string_field, get_timeout_field, names::*};
 
 const SKIP_FIELDS: [&str; 2] = [INSTALLER_LOCATION, INSTALL_ERROR];
 
+/// How the install parameters are passed to Linux packages
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstallParametersMode {
+    /// Environment variables of the package manager process. Used by RPM packages
+    #[default]
+    Env,
+    /// Debconf preseed loaded with debconf-set-selections before invoking dpkg
+    Debconf
+}
+
+impl TryFrom<&str> for InstallParametersMode {
+    type Error = ChaosError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value.to_lowercase().as_str() {
+            "env" => InstallParametersMode::Env,
+            "debconf" => InstallParametersMode::Debconf,
+            _ => return Err(ChaosError::Other(format!("Invalid install parameters mode {}, expected env or debconf", value)))
+        })
+    }
+}
+
 /// Installation parameters: installer msi in windows or package in linux and parameters to be passed to the installer program
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct InstallParameters {
@@ -15,6 +36,8 @@ pub struct InstallParameters {
     pub installer: String,
     /// List of parameters to pass to the installer
     pub parameters: BTreeMap<String, String>,
+    /// How the parameters reach Linux packages
+    pub parameters_mode: InstallParametersMode,
     /// 60 seconds by default
     pub timeout: Duration,
 }
@@ -27,18 +50,33 @@ pub struct InstallWithErrorParameters {
     pub installer: String,
     /// List of parameters to pass to the installer
     pub parameters: BTreeMap<String, String>,
+    /// How the parameters reach Linux packages
+    pub parameters_mode: InstallParametersMode,
     pub error: i32,
     /// 60 seconds by default
     pub timeout: Duration,
 }
 
+/// Installation interrupted by a signal: the installer is killed after a delay or when a file appears
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InstallInterruptedParameters {
+    pub install: InstallParameters,
+    /// SIGKILL by default. Parsed by the agent like process_signal
+    pub signal: String,
+    /// 2 seconds by default. Maximum wait when the interrupt file is used
+    pub delay: Duration,
+    pub file: Option<String>,
+}
+
 /// Installation check parameters: 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct InstallCheckParameters {
     /// Product Code GUID
     pub product_code: Option<String>,
     /// Name of the product
-    pub product_name: String
+    pub product_name: String,
+    /// Version that must be installed
+    pub expected_version: Option<String>
 }
 
 
@@ -48,6 +86,7 @@ impl TryFrom<&TestParameters> for InstallParameters {
         let installer = get_installer_field(params)?;
         let timeout = get_timeout_field(params).unwrap_or_else(|_| Duration::from_secs(60));
         let install_parameters = get_install_parameters_field(params)?;
+        let parameters_mode = get_install_parameters_mode(params)?;
 
         let mut parameters = BTreeMap::new();
 
@@ -67,6 +106,7 @@ impl TryFrom<&TestParameters> for InstallParameters {
         Ok(InstallParameters {
             installer,
             parameters,
+            parameters_mode,
             timeout,
         })
     }
@@ -84,6 +124,7 @@ impl TryFrom<&TestParameters> for InstallWithErrorParameters {
         let installer = get_installer_field(params)?;
         let timeout = get_timeout_field(params).unwrap_or_else(|_| Duration::from_secs(60));
         let install_parameters = get_install_parameters_field(params)?;
+        let parameters_mode = get_install_parameters_mode(params)?;
 
         let error = get_install_error(params)?;
         
@@ -105,6 +146,7 @@ impl TryFrom<&TestParameters> for InstallWithErrorParameters {
         Ok(InstallWithErrorParameters {
             installer,
             parameters,
+            parameters_mode,
             error,
             timeout,
         })
@@ -117,15 +159,45 @@ impl TryFrom<TestParameters> for InstallParameters {
     }
 }
 
+impl TryFrom<&TestParameters> for InstallInterruptedParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let install = InstallParameters::try_from(params)?;
+        let signal = get_string_field(params, INTERRUPT_SIGNAL).unwrap_or_else(|_| "SIGKILL".to_string());
+        let file = get_string_field(params, INTERRUPT_FILE).ok();
+        // Without trigger the installer is interrupted after 2 seconds
+        let delay = match params.get(INTERRUPT_DELAY) {
+            Some(_) => get_duration_field(params, INTERRUPT_DELAY)?,
+            None if file.is_some() => install.timeout,
+            None => Duration::from_secs(2),
+        };
+        Ok(InstallInterruptedParameters {
+            install,
+            signal,
+            delay,
+            file,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for InstallInterruptedParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
 impl TryFrom<&TestParameters> for InstallCheckParameters {
     type Error = ChaosError;
     fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
         let product_code = get_string_field(params, "product_code").ok();
         let product_name = get_string_field(params, "product_name")?;
+        let expected_version = get_string_field(params, EXPECTED_VERSION).ok();
 
         Ok(InstallCheckParameters {
             product_code,
-            product_name
+            product_name,
+            expected_version
         })
     }
 }
@@ -160,6 +232,13 @@ pub fn get_install_parameters_field(parameters : &TestParameters) -> ChaosResult
         .map_err(|_| "Invalid parameter type, expected Obj".to_string())?)
 }
 
+pub fn get_install_parameters_mode(parameters : &TestParameters) -> ChaosResult<InstallParametersMode> {
+    match get_string_field(parameters, INSTALL_PARAMETERS_MODE) {
+        Ok(v) => InstallParametersMode::try_from(v.as_str()),
+        Err(_) => Ok(InstallParametersMode::default())
+    }
+}
+
 pub fn get_install_error(parameters : &TestParameters) -> ChaosResult<i32> {
     Ok(parameters
         .get(INSTALL_ERROR)
@@ -169,4 +248,30 @@ pub fn get_install_error(parameters : &TestParameters) -> ChaosResult<i32> {
         ))?
         .try_into()
         .map_err(|_| "Invalid parameter type, expected i32".to_string())?)
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_parse_install_interrupted_parameters() {
+    let mut install_parameters = BTreeMap::new();
+    install_parameters.insert("SERVER".to_string(), TestParameter::Text("127.0.0.1".into()));
+    let mut params = TestParameters::new();
+    params.insert(INSTALLER_LOCATION, TestParameter::Text("chaos-bench.deb".into()));
+    params.insert(INSTALL_PARAMETERS, TestParameter::Obj(install_parameters));
+    params.insert(TASK_TIMEOUT, TestParameter::Text("30s".into()));
+    let interrupted : InstallInterruptedParameters = (&params).try_into().unwrap();
+    assert_eq!("SIGKILL", interrupted.signal);
+    assert_eq!(Duration::from_secs(2), interrupted.delay);
+    assert_eq!(None, interrupted.file);
+    params.insert(INTERRUPT_FILE, TestParameter::Text("/var/lib/dpkg/info/chaos-bench.postinst".into()));
+    params.insert(INTERRUPT_SIGNAL, TestParameter::Text("term".into()));
+    let interrupted : InstallInterruptedParameters = (&params).try_into().unwrap();
+    assert_eq!("term", interrupted.signal);
+    assert_eq!(Duration::from_secs(30), interrupted.delay);
+    assert_eq!(Some("/var/lib/dpkg/info/chaos-bench.postinst".to_string()), interrupted.file);
+    params.insert(INTERRUPT_DELAY, TestParameter::Text("500ms".into()));
+    params.insert(INTERRUPT_SIGNAL, TestParameter::Text("9".into()));
+    let interrupted : InstallInterruptedParameters = (&params).try_into().unwrap();
+    assert_eq!(Duration::from_millis(500), interrupted.delay);
+    params.insert(INTERRUPT_DELAY, TestParameter::Bool(true));
+    assert!(InstallInterruptedParameters::try_from(&params).is_err());
+}
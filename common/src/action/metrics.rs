@@ -1,20 +1,314 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, fmt::Display, time::Duration};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{parameters::TestParameters, err::ChaosError};
+use crate::{parameters::{TestParameter, TestParameters}, err::ChaosError};
 
-use super::{get_duration_field, get_f32_field, get_string_field, get_u64_field, names::*};
+use super::{get_duration_field, get_string_field, names::*};
+
+/// Metrics that accept thresholds
+pub const METRIC_NAMES : [&str; 9] = ["cpu", "ram", "fds", "threads", "sockets", "read_bytes", "write_bytes", "voluntary_switches", "involuntary_switches"];
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MetricsArtifact {
     pub ram_samples : Vec<u64>,
     pub cpu_samples : Vec<f64>,
+    /// (Linux) Open file descriptors
+    #[serde(default)]
+    pub fd_samples : Vec<u64>,
+    #[serde(default)]
+    pub thread_samples : Vec<u64>,
+    /// (Linux) Open sockets
+    #[serde(default)]
+    pub socket_samples : Vec<u64>,
+    /// (Linux) Bytes read from storage since the previous sample
+    #[serde(default)]
+    pub read_bytes_samples : Vec<u64>,
+    /// (Linux) Bytes written to storage since the previous sample
+    #[serde(default)]
+    pub write_bytes_samples : Vec<u64>,
+    /// (Linux) Context switches since the previous sample
+    #[serde(default)]
+    pub voluntary_switch_samples : Vec<u64>,
+    #[serde(default)]
+    pub involuntary_switch_samples : Vec<u64>,
     pub start_time : i64,
     pub end_time : i64,
     pub freq : Duration
 }
 
+impl MetricsArtifact {
+    /// Statistics of each metric. The metrics without samples in this platform are not included
+    pub fn stats(&self) -> BTreeMap<String, MetricStats> {
+        let mut stats = BTreeMap::new();
+        stats.insert("cpu".to_string(), MetricStats::new(self.cpu_samples.iter().copied()));
+        stats.insert("ram".to_string(), MetricStats::new(self.ram_samples.iter().map(|v| *v as f64)));
+        let counters = [
+            ("fds", &self.fd_samples),
+            ("threads", &self.thread_samples),
+            ("sockets", &self.socket_samples),
+            ("read_bytes", &self.read_bytes_samples),
+            ("write_bytes", &self.write_bytes_samples),
+            ("voluntary_switches", &self.voluntary_switch_samples),
+            ("involuntary_switches", &self.involuntary_switch_samples),
+        ];
+        for (metric, samples) in counters {
+            if !samples.is_empty() {
+                stats.insert(metric.to_string(), MetricStats::new(samples.iter().map(|v| *v as f64)));
+            }
+        }
+        stats
+    }
+
+    /// Trend of the RAM samples taken after the warm-up. The samples are spread evenly between start_time and end_time
+    pub fn ram_trend(&self, warmup : Duration) -> Option<LinearTrend> {
+        if self.ram_samples.is_empty() {
+            return None
+        }
+        let step_ms = (self.end_time - self.start_time).max(0) as f64 / self.ram_samples.len() as f64;
+        let warmup_ms = warmup.as_millis() as f64;
+        let points : Vec<(f64, f64)> = self.ram_samples.iter().enumerate()
+            .map(|(i, v)| (i as f64 * step_ms, *v as f64))
+            .filter(|(time, _)| *time >= warmup_ms)
+            .collect();
+        LinearTrend::fit(&points)
+    }
+}
+
+/// Least squares line of the samples of a metric
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinearTrend {
+    /// Growth of the metric per hour. Ex: bytes per hour
+    pub slope_per_hour : f64,
+    /// Coefficient of determination (0 to 1). How well the line explains the samples
+    pub r_squared : f64,
+    pub samples : usize
+}
+
+impl LinearTrend {
+    /// points: (milliseconds, value). At least 3 points with different times
+    pub fn fit(points : &[(f64, f64)]) -> Option<Self> {
+        if points.len() < 3 {
+            return None
+        }
+        let count = points.len() as f64;
+        let mean_x = points.iter().map(|v| v.0).sum::<f64>() / count;
+        let mean_y = points.iter().map(|v| v.1).sum::<f64>() / count;
+        let sxx : f64 = points.iter().map(|v| (v.0 - mean_x) * (v.0 - mean_x)).sum();
+        let sxy : f64 = points.iter().map(|v| (v.0 - mean_x) * (v.1 - mean_y)).sum();
+        let syy : f64 = points.iter().map(|v| (v.1 - mean_y) * (v.1 - mean_y)).sum();
+        if sxx == 0.0 {
+            return None
+        }
+        let slope = sxy / sxx;
+        // A flat metric is perfectly explained by the line
+        let r_squared = if syy == 0.0 { 1.0 } else { (sxy * sxy) / (sxx * syy) };
+        Some(Self {
+            slope_per_hour : slope * 3_600_000.0,
+            r_squared,
+            samples : points.len()
+        })
+    }
+}
+
+impl From<&LinearTrend> for TestParameter {
+    fn from(value: &LinearTrend) -> Self {
+        let mut trend = BTreeMap::new();
+        trend.insert("slope_per_hour".to_string(), TestParameter::F64(value.slope_per_hour));
+        trend.insert("r_squared".to_string(), TestParameter::F64(value.r_squared));
+        trend.insert("samples".to_string(), TestParameter::U64(value.samples as u64));
+        TestParameter::Obj(trend)
+    }
+}
+
+/// Leak check parameters:
+/// metric_executable_path: Process measured by StartMetricsForProcess. The service of service_name if not present
+/// metric_leak_max_growth: Max RAM growth in bytes per hour
+/// metric_leak_warmup: Time ignored at the start of the metrics. 0 if not present
+/// metric_leak_min_confidence: Min R² (0 to 1) of the trend to consider the growth a leak. 0.8 if not present
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AssertNoLeakParameters {
+    pub executable_path : Option<String>,
+    pub service_name : Option<String>,
+    pub max_growth : f64,
+    pub warmup : Duration,
+    pub min_confidence : f64
+}
+
+impl AssertNoLeakParameters {
+    pub fn check(&self, trend : &LinearTrend) -> Result<(), ChaosError> {
+        if trend.slope_per_hour > self.max_growth && trend.r_squared >= self.min_confidence {
+            return Err(ChaosError::Other(format!("RAM grows {:.0} bytes/hour (R²={:.2}, {} samples), max {:.0} bytes/hour", trend.slope_per_hour, trend.r_squared, trend.samples, self.max_growth)))
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&TestParameters> for AssertNoLeakParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let executable_path = get_string_field(params, "metric_executable_path").ok();
+        let service_name = get_string_field(params, APP_SERVICE_NAME).ok();
+        if executable_path.is_none() && service_name.is_none() {
+            return Err(ChaosError::Other(format!("Parameter {:?} or {:?} not found", "metric_executable_path", APP_SERVICE_NAME)))
+        }
+        let max_growth : f64 = params.get(METRIC_LEAK_MAX_GROWTH)
+            .ok_or_else(|| ChaosError::Other(format!("Parameter {:?} not found", METRIC_LEAK_MAX_GROWTH)))?
+            .try_into()
+            .map_err(|_| ChaosError::Other(format!("Invalid parameter {:?}, expected a number", METRIC_LEAK_MAX_GROWTH)))?;
+        let warmup = get_duration_field(params, METRIC_LEAK_WARMUP).unwrap_or_default();
+        let min_confidence = params.get(METRIC_LEAK_MIN_CONFIDENCE).and_then(|v| f64::try_from(v).ok()).unwrap_or(0.8);
+        Ok(Self {
+            executable_path,
+            service_name,
+            max_growth,
+            warmup,
+            min_confidence
+        })
+    }
+}
+impl TryFrom<TestParameters> for AssertNoLeakParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricStat {
+    Avg,
+    Max,
+    P50,
+    P95,
+    P99,
+    StdDev
+}
+
+impl MetricStat {
+    pub const ALL : [MetricStat; 6] = [MetricStat::Avg, MetricStat::Max, MetricStat::P50, MetricStat::P95, MetricStat::P99, MetricStat::StdDev];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetricStat::Avg => "avg",
+            MetricStat::Max => "max",
+            MetricStat::P50 => "p50",
+            MetricStat::P95 => "p95",
+            MetricStat::P99 => "p99",
+            MetricStat::StdDev => "std_dev",
+        }
+    }
+}
+
+/// Statistics of the samples of a metric
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricStats {
+    pub samples : usize,
+    pub avg : f64,
+    pub max : f64,
+    pub p50 : f64,
+    pub p95 : f64,
+    pub p99 : f64,
+    pub std_dev : f64
+}
+
+impl MetricStats {
+    pub fn new(samples : impl Iterator<Item = f64>) -> Self {
+        let mut sorted : Vec<f64> = samples.filter(|v| v.is_finite()).collect();
+        if sorted.is_empty() {
+            return Self::default()
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let count = sorted.len() as f64;
+        let avg = sorted.iter().sum::<f64>() / count;
+        let variance = sorted.iter().map(|v| (v - avg) * (v - avg)).sum::<f64>() / count;
+        Self {
+            samples : sorted.len(),
+            avg,
+            max : sorted[sorted.len() - 1],
+            p50 : percentile(&sorted, 50.0),
+            p95 : percentile(&sorted, 95.0),
+            p99 : percentile(&sorted, 99.0),
+            std_dev : variance.sqrt()
+        }
+    }
+
+    pub fn get(&self, stat : MetricStat) -> f64 {
+        match stat {
+            MetricStat::Avg => self.avg,
+            MetricStat::Max => self.max,
+            MetricStat::P50 => self.p50,
+            MetricStat::P95 => self.p95,
+            MetricStat::P99 => self.p99,
+            MetricStat::StdDev => self.std_dev,
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted : &[f64], percentile : f64) -> f64 {
+    let rank = ((percentile / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Display for MetricStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "avg={:.2} max={:.2} p50={:.2} p95={:.2} p99={:.2} std_dev={:.2} samples={}", self.avg, self.max, self.p50, self.p95, self.p99, self.std_dev, self.samples)
+    }
+}
+
+impl From<&MetricStats> for TestParameter {
+    fn from(value: &MetricStats) -> Self {
+        let mut stats = BTreeMap::new();
+        for stat in MetricStat::ALL {
+            stats.insert(stat.name().to_string(), TestParameter::F64(value.get(stat)));
+        }
+        stats.insert("samples".to_string(), TestParameter::U64(value.samples as u64));
+        TestParameter::Obj(stats)
+    }
+}
+
+/// Upper limit of a statistic of a metric. Parameter metric_max_{stat}_{metric}. Ex: metric_max_p95_cpu
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricThreshold {
+    pub metric : String,
+    pub stat : MetricStat,
+    pub max : f64
+}
+
+impl MetricThreshold {
+    /// Error if the statistic is over the limit
+    pub fn check(&self, stats : &BTreeMap<String, MetricStats>) -> Result<(), String> {
+        let value = match stats.get(&self.metric) {
+            Some(v) if v.samples > 0 => v.get(self.stat),
+            _ => return Ok(())
+        };
+        if value > self.max {
+            return Err(format!("{} {} larger than expected: {:.2} vs {}", self.stat.name(), self.metric, value, self.max))
+        }
+        Ok(())
+    }
+}
+
+fn get_thresholds(params : &TestParameters) -> Result<Vec<MetricThreshold>, ChaosError> {
+    let mut thresholds = Vec::new();
+    for metric in METRIC_NAMES {
+        for stat in MetricStat::ALL {
+            let name = format!("metric_max_{}_{}", stat.name(), metric);
+            let max : f64 = match params.get(&name) {
+                Some(v) => v.try_into().map_err(|_| ChaosError::Other(format!("Invalid parameter {:?}, expected a number", name)))?,
+                None => continue
+            };
+            thresholds.push(MetricThreshold { metric : metric.to_string(), stat, max });
+        }
+    }
+    Ok(thresholds)
+}
+
+/// Statistics of each metric. Ex: cpu: avg=1.00 ...; ram: avg=...
+pub fn format_metric_stats(stats : &BTreeMap<String, MetricStats>) -> String {
+    stats.iter().map(|(metric, stats)| format!("{}: {}", metric, stats)).collect::<Vec<String>>().join("; ")
+}
+
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct StartMetricsForService {
     /// Name of the service 
@@ -47,32 +341,26 @@ pub struct UploadMetricsForProcess {
 pub struct StopMetricsForService {
     /// Name of the service 
     pub service_name: String,
-    /// Max CPU permitted for the service in % (Max 1.0 for 1 core)
-    pub max_average_cpu : f32,
-    /// Max PhysicalMemory permitted for the service in bytes
-    pub max_average_ram : u64
+    /// Limits of the metric statistics. CPU in % (Max 1.0 for 1 core), RAM in bytes, I/O in bytes per sample
+    pub thresholds : Vec<MetricThreshold>
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct StopMetricsForProcess {
     /// Full path of the executable.
     pub executable_path: String,
-    /// Max CPU permitted for the process in % (Max 1.0 for 1 core)
-    pub max_average_cpu : f32,
-    /// Max PhysicalMemory permitted for the process in bytes
-    pub max_average_ram : u64
+    /// Limits of the metric statistics. CPU in % (Max 1.0 for 1 core), RAM in bytes, I/O in bytes per sample
+    pub thresholds : Vec<MetricThreshold>
 }
 
 impl TryFrom<&TestParameters> for StopMetricsForService {
     type Error = ChaosError;
     fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
         let service_name = get_string_field(params, APP_SERVICE_NAME)?;
-        let max_average_ram = get_u64_field(params, "metric_max_avg_ram")?;
-        let max_average_cpu = get_f32_field(params, "metric_max_avg_cpu")?;
+        let thresholds = get_thresholds(params)?;
         Ok(Self {
             service_name,
-            max_average_cpu,
-            max_average_ram,
+            thresholds,
         })
     }
 }
@@ -87,12 +375,10 @@ impl TryFrom<&TestParameters> for StopMetricsForProcess {
     type Error = ChaosError;
     fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
         let executable_path = get_string_field(params, "metric_executable_path")?;
-        let max_average_ram = get_u64_field(params, "metric_max_avg_ram")?;
-        let max_average_cpu = get_f32_field(params, "metric_max_avg_cpu")?;
+        let thresholds = get_thresholds(params)?;
         Ok(Self {
             executable_path,
-            max_average_cpu,
-            max_average_ram,
+            thresholds,
         })
     }
 }
@@ -167,4 +453,60 @@ impl TryFrom<TestParameters> for UploadMetricsForProcess {
     fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
         (&value).try_into()
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn should_calculate_metric_stats() {
+    let mut samples : Vec<f64> = vec![10.0; 90];
+    samples.extend([100.0; 10]);
+    let stats = MetricStats::new(samples.into_iter());
+    assert_eq!(100, stats.samples);
+    assert_eq!(19.0, stats.avg);
+    assert_eq!(100.0, stats.max);
+    assert_eq!(10.0, stats.p50);
+    assert_eq!(100.0, stats.p95);
+    assert_eq!(27.0, stats.std_dev);
+    let mut all = BTreeMap::new();
+    all.insert("cpu".to_string(), stats);
+    let mut params = TestParameters::new();
+    params.insert("metric_max_avg_cpu", TestParameter::F64(20.0));
+    params.insert("metric_max_p95_cpu", TestParameter::U64(80));
+    let thresholds = get_thresholds(&params).unwrap();
+    assert!(thresholds[0].check(&all).is_ok());
+    assert_eq!(Err("p95 cpu larger than expected: 100.00 vs 80".to_string()), thresholds[1].check(&all));
+    assert_eq!(MetricStats::default(), MetricStats::new(std::iter::empty()));
+    let artifact = MetricsArtifact {
+        fd_samples : vec![10, 12, 250],
+        ..Default::default()
+    };
+    let stats = artifact.stats();
+    assert_eq!(3, stats.len());
+    params.insert("metric_max_max_fds", TestParameter::U64(200));
+    let thresholds = get_thresholds(&params).unwrap();
+    assert_eq!(Err("max fds larger than expected: 250.00 vs 200".to_string()), thresholds[2].check(&stats));
+}
+
+#[test]
+fn should_detect_ram_trend() {
+    let params = AssertNoLeakParameters { max_growth : 1_000_000.0, min_confidence : 0.8, ..Default::default() };
+    // 10 MB/hour with noise, sampled each minute for an hour
+    let mut artifact = MetricsArtifact {
+        ram_samples : (0..60u64).map(|i| 100_000_000 + i * 166_667 + (i % 3) * 50_000).collect(),
+        start_time : 0,
+        end_time : 3_600_000,
+        ..Default::default()
+    };
+    let trend = artifact.ram_trend(Duration::ZERO).unwrap();
+    assert!((trend.slope_per_hour - 10_000_000.0).abs() < 100_000.0);
+    assert!(trend.r_squared > 0.99);
+    assert!(params.check(&trend).is_err());
+    // Grows during the first 10 minutes and then stays flat. A line doesn't explain it
+    artifact.ram_samples = (0..60u64).map(|i| 100_000_000 + i.min(10) * 1_000_000 + (i % 2) * 200_000).collect();
+    let trend = artifact.ram_trend(Duration::ZERO).unwrap();
+    assert!(trend.slope_per_hour > params.max_growth && trend.r_squared < 0.5);
+    assert!(params.check(&trend).is_ok());
+    let trend = artifact.ram_trend(Duration::from_secs(660)).unwrap();
+    assert_eq!(49, trend.samples);
+    assert!(params.check(&trend).is_ok());
+    assert!(artifact.ram_trend(Duration::from_secs(3600)).is_none());
+}
@@ -12,13 +12,24 @@ use crate::{
 
 use self::names::TASK_TIMEOUT;
 
+pub mod audit;
 pub mod dns;
 pub mod download;
 pub mod execute;
+pub mod filesystem;
+pub mod firewall;
+pub mod inspect;
 pub mod install;
 pub mod metrics;
 pub mod names;
+pub mod network;
+pub mod process;
 pub mod service;
+pub mod snapshot;
+pub mod soak;
+pub mod stress;
+pub mod time;
+pub mod trace;
 pub mod upload;
 pub mod wait;
 pub mod watchlog;
@@ -33,9 +44,22 @@ pub enum TestActionType {
     Log(LogActionType),
     Artifact(ArtifactActionType),
     Dns(DnsActionType),
+    Snapshot(SnapshotActionType),
+    Trace(TraceActionType),
+    Audit(AuditActionType),
+    Process(ProcessActionType),
+    Network(NetworkActionType),
+    Firewall(FirewallActionType),
+    Stress(StressActionType),
+    Filesystem(FilesystemActionType),
+    Time(TimeActionType),
+    Limits(LimitsActionType),
+    Chaos(ChaosActionType),
     RestartHost,
     /// Wait some time
     Wait,
+    /// Runs checks at an interval during some time
+    Soak,
     /// Cleans the temporal folder associated with this test, not the real TMP folder
     CleanTmpFolder,
     CleanAppFolder,
@@ -63,7 +87,19 @@ impl<'a> From<&'a TestActionType> for &'a str {
         match value {
             TestActionType::Package(v) => v.into(),
             TestActionType::Dns(v) => v.into(),
+            TestActionType::Snapshot(v) => v.into(),
+            TestActionType::Trace(v) => v.into(),
+            TestActionType::Audit(v) => v.into(),
+            TestActionType::Process(v) => v.into(),
+            TestActionType::Network(v) => v.into(),
+            TestActionType::Firewall(v) => v.into(),
+            TestActionType::Stress(v) => v.into(),
+            TestActionType::Filesystem(v) => v.into(),
+            TestActionType::Time(v) => v.into(),
+            TestActionType::Limits(v) => v.into(),
+            TestActionType::Chaos(v) => v.into(),
             TestActionType::Wait => "Wait",
+            TestActionType::Soak => "Soak",
             TestActionType::RestartHost => "RestartHost",
             TestActionType::Execute(v) => v.into(),
             TestActionType::CleanTmpFolder => "CleanTmpFolder",
@@ -130,6 +166,7 @@ impl From<&str> for TestActionType {
             "CloseUserSession" => TestActionType::CloseUserSession,
             "Null" => TestActionType::Null,
             "Wait" => TestActionType::Wait,
+            "Soak" => TestActionType::Soak,
             _ => from_str_to_test_action_type(value),
         }
     }
@@ -148,6 +185,7 @@ pub fn from_str_to_test_action_type(value: &str) -> TestActionType {
         "CloseUserSession" => TestActionType::CloseUserSession,
         "Null" => TestActionType::Null,
         "Wait" => TestActionType::Wait,
+            "Soak" => TestActionType::Soak,
         _ => from_str_to_test_action_type_subpart(value)
             .unwrap_or_else(|| TestActionType::Custom(value.into())),
     }
@@ -160,11 +198,23 @@ fn from_str_to_test_action_type_subpart(value: &str) -> Option<TestActionType> {
         "Package" => TestActionType::Package(value.try_into().ok()?),
         "Service" => TestActionType::Service(value.try_into().ok()?),
         "Execute" => TestActionType::Execute(value.try_into().ok()?),
-        "Metrics" => TestActionType::Metrics(value.try_into().ok()?),
+        "Metric" | "Metrics" => TestActionType::Metrics(value.try_into().ok()?),
         "Http" => TestActionType::Http(value.try_into().ok()?),
         "Log" => TestActionType::Log(value.try_into().ok()?),
         "Artifact" => TestActionType::Artifact(value.try_into().ok()?),
         "Dns" => TestActionType::Dns(value.try_into().ok()?),
+        "Snapshot" => TestActionType::Snapshot(value.try_into().ok()?),
+        "Trace" => TestActionType::Trace(value.try_into().ok()?),
+        "Audit" => TestActionType::Audit(value.try_into().ok()?),
+        "Process" => TestActionType::Process(value.try_into().ok()?),
+        "Network" => TestActionType::Network(value.try_into().ok()?),
+        "Firewall" => TestActionType::Firewall(value.try_into().ok()?),
+        "Stress" => TestActionType::Stress(value.try_into().ok()?),
+        "Filesystem" => TestActionType::Filesystem(value.try_into().ok()?),
+        "Time" => TestActionType::Time(value.try_into().ok()?),
+        "Limits" => TestActionType::Limits(value.try_into().ok()?),
+        "Chaos" => TestActionType::Chaos(value.try_into().ok()?),
+        v if v.starts_with("Service:") => TestActionType::Service(value.try_into().ok()?),
         _ => return None,
     })
 }
@@ -265,8 +315,34 @@ impl TestActionType {
                 | TestActionType::Log(LogActionType::Watch)
                 | TestActionType::Metrics(MetricActionType::StartMetricsForProcess)
                 | TestActionType::Metrics(MetricActionType::StartMetricsForService)
+                | TestActionType::Trace(TraceActionType::Start)
+                | TestActionType::Network(NetworkActionType::Degrade)
+                | TestActionType::Firewall(FirewallActionType::Block)
+                | TestActionType::Stress(StressActionType::Cpu)
+                | TestActionType::Stress(StressActionType::Memory)
+                | TestActionType::Stress(StressActionType::Io)
+                | TestActionType::Filesystem(FilesystemActionType::Fill)
+                | TestActionType::Filesystem(FilesystemActionType::ReadOnly)
+                | TestActionType::Filesystem(FilesystemActionType::Tmpfs)
+                | TestActionType::Time(TimeActionType::Shift)
+                | TestActionType::Time(TimeActionType::Freeze)
+                | TestActionType::Limits(LimitsActionType::Apply)
         )
     }
+
+    /// Action that removes the fault injected by this one. Scenes end with it if no later phase removes the fault
+    pub fn undo_action(&self) -> Option<TestActionType> {
+        Some(match self {
+            TestActionType::Process(ProcessActionType::Suspend) => TestActionType::Process(ProcessActionType::Resume),
+            TestActionType::Network(NetworkActionType::Degrade) => TestActionType::Network(NetworkActionType::Restore),
+            TestActionType::Firewall(FirewallActionType::Block) => TestActionType::Firewall(FirewallActionType::Unblock),
+            TestActionType::Stress(StressActionType::Cpu | StressActionType::Memory | StressActionType::Io) => TestActionType::Stress(StressActionType::Stop),
+            TestActionType::Filesystem(FilesystemActionType::Fill | FilesystemActionType::ReadOnly | FilesystemActionType::Tmpfs) => TestActionType::Filesystem(FilesystemActionType::Release),
+            TestActionType::Time(TimeActionType::Shift | TimeActionType::Freeze) => TestActionType::Time(TimeActionType::Restore),
+            TestActionType::Limits(LimitsActionType::Apply) => TestActionType::Limits(LimitsActionType::Reset),
+            _ => return None,
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
@@ -275,12 +351,18 @@ pub enum PackageActionType {
     Install,
     /// Uninstall the application
     Uninstall,
+    /// Upgrade the installed application in place
+    Upgrade,
     /// Try to install the application, but cant be done
     InstallWithError,
     /// Check that the application is installed
     IsInstalled,
     /// Check that the application is not installed
     IsNotInstalled,
+    /// Static checks of the package without installing it
+    Inspect,
+    /// Kill the installer mid-flight and check that a reinstall recovers the system
+    InstallInterrupted,
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
@@ -293,6 +375,8 @@ pub enum ServiceActionType {
     Start,
     /// Checks that the service is running
     IsRunning,
+    /// Kills the service main process and waits for the service manager to restart it
+    CrashRecovery,
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
@@ -321,6 +405,8 @@ pub enum MetricActionType {
     StopMetricsForService,
     /// Uploads metrics of a service
     UploadServiceMetrics,
+    /// Fails if the RAM of the measured process or service grows over time
+    AssertNoLeak,
 }
 
 #[derive(Clone, Debug, PartialEq, Hash)]
@@ -357,6 +443,104 @@ pub enum DnsActionType {
     Remove,
 }
 
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum SnapshotActionType {
+    /// Records files, packages, services, users, cron entries and hosts of the system
+    Take,
+    /// Fails if the system differs from a previous snapshot
+    Compare,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum TraceActionType {
+    /// Starts recording the filesystem activity under the trace roots
+    Start,
+    /// Stops recording and uploads the filesystem activity
+    Stop,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum AuditActionType {
+    /// Checks modes, owners and setuid bits of the files in the application folders
+    Permissions,
+    /// Checks the ports opened by the application processes against an allowlist
+    ListeningPorts,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum ProcessActionType {
+    /// Sends a signal to the application process. SIGKILL by default
+    Kill,
+    /// Stops the application process with SIGSTOP
+    Suspend,
+    /// Continues the application process with SIGCONT
+    Resume,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum NetworkActionType {
+    /// Applies latency, packet loss, corruption or rate limits on an interface
+    Degrade,
+    /// Removes the network faults
+    Restore,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum FirewallActionType {
+    /// Drops or rejects the traffic to a host or port
+    Block,
+    /// Removes the firewall rules created by Block
+    Unblock,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum StressActionType {
+    /// Busy threads
+    Cpu,
+    /// Allocates and touches memory
+    Memory,
+    /// Write and fsync loops in the temp workspace
+    Io,
+    /// Stops all the load generators
+    Stop,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum FilesystemActionType {
+    /// Creates a filler file leaving only some bytes free in the volume
+    Fill,
+    /// Bind mounts a folder over itself as read-only
+    ReadOnly,
+    /// Mounts a small tmpfs over a folder
+    Tmpfs,
+    /// Removes the filler files and the mounts
+    Release,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum TimeActionType {
+    /// Moves the system clock by an offset
+    Shift,
+    /// Stops the clock of the applications started under libfaketime
+    Freeze,
+    /// Moves the system clock back to the real time and unfreezes libfaketime
+    Restore,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum LimitsActionType {
+    /// Restarts the service with resource limits
+    Apply,
+    /// Restarts the service without the resource limits
+    Reset,
+}
+
+#[derive(Clone, Debug, PartialEq, Hash)]
+pub enum ChaosActionType {
+    /// Faults picked from the chaos pool of the scenario. Replaced by the server with the faults and the waits between them
+    Random,
+}
+
 impl<'a> From<&'a ArtifactActionType> for &'a str {
     fn from(value: &ArtifactActionType) -> &str {
         match value {
@@ -425,19 +609,23 @@ impl<'a> From<&'a MetricActionType> for &'a str {
             MetricActionType::StartMetricsForService => "Metric::StartMetricsForService",
             MetricActionType::StopMetricsForService => "Metric::StopMetricsForService",
             MetricActionType::UploadServiceMetrics => "Metric::UploadServiceMetrics",
+            MetricActionType::AssertNoLeak => "Metric::AssertNoLeak",
         }
     }
 }
 impl TryFrom<&str> for MetricActionType {
     type Error = &'static str;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        // Metric::X or Metrics::X
+        let value = value.strip_prefix("Metric::").or_else(|| value.strip_prefix("Metrics::")).ok_or("Invalid Metric action type")?;
         Ok(match value {
-            "Metric::StartMetricsForProcess" => MetricActionType::StartMetricsForProcess,
-            "Metric::StopMetricsForProcess" => MetricActionType::StopMetricsForProcess,
-            "Metric::UploadProcessMetrics" => MetricActionType::UploadProcessMetrics,
-            "Metric::StartMetricsForService" => MetricActionType::StartMetricsForService,
-            "Metric::StopMetricsForService" => MetricActionType::StopMetricsForService,
-            "Metric::UploadServiceMetrics" => MetricActionType::UploadServiceMetrics,
+            "StartMetricsForProcess" => MetricActionType::StartMetricsForProcess,
+            "StopMetricsForProcess" => MetricActionType::StopMetricsForProcess,
+            "UploadProcessMetrics" => MetricActionType::UploadProcessMetrics,
+            "StartMetricsForService" => MetricActionType::StartMetricsForService,
+            "StopMetricsForService" => MetricActionType::StopMetricsForService,
+            "UploadServiceMetrics" => MetricActionType::UploadServiceMetrics,
+            "AssertNoLeak" => MetricActionType::AssertNoLeak,
             _ => return Err("Invalid Metric action type"),
         })
     }
@@ -446,21 +634,25 @@ impl TryFrom<&str> for MetricActionType {
 impl<'a> From<&'a ServiceActionType> for &'a str {
     fn from(value: &ServiceActionType) -> &str {
         match value {
-            ServiceActionType::Restart => "Service:Restart",
-            ServiceActionType::Stop => "Service:Stop",
-            ServiceActionType::Start => "Service:Start",
-            ServiceActionType::IsRunning => "Service:IsRunning",
+            ServiceActionType::Restart => "Service::Restart",
+            ServiceActionType::Stop => "Service::Stop",
+            ServiceActionType::Start => "Service::Start",
+            ServiceActionType::IsRunning => "Service::IsRunning",
+            ServiceActionType::CrashRecovery => "Service::CrashRecovery",
         }
     }
 }
 impl TryFrom<&str> for ServiceActionType {
     type Error = &'static str;
     fn try_from(value: &str) -> Result<Self, Self::Error> {
+        // Service::X or the legacy Service:X
+        let value = value.strip_prefix("Service::").or_else(|| value.strip_prefix("Service:")).ok_or("Invalid Service action type")?;
         Ok(match value {
-            "Service:Restart" => ServiceActionType::Restart,
-            "Service:Stop" => ServiceActionType::Stop,
-            "Service:Start" => ServiceActionType::Start,
-            "Service:IsRunning" => ServiceActionType::IsRunning,
+            "Restart" => ServiceActionType::Restart,
+            "Stop" => ServiceActionType::Stop,
+            "Start" => ServiceActionType::Start,
+            "IsRunning" => ServiceActionType::IsRunning,
+            "CrashRecovery" => ServiceActionType::CrashRecovery,
             _ => return Err("Invalid Service action type"),
         })
     }
@@ -494,9 +686,12 @@ impl<'a> From<&'a PackageActionType> for &'a str {
         match value {
             PackageActionType::Install => "Package::Install",
             PackageActionType::Uninstall => "Package::Uninstall",
+            PackageActionType::Upgrade => "Package::Upgrade",
             PackageActionType::InstallWithError => "Package::InstallWithError",
             PackageActionType::IsInstalled => "Package::IsInstalled",
             PackageActionType::IsNotInstalled => "Package::IsNotInstalled",
+            PackageActionType::Inspect => "Package::Inspect",
+            PackageActionType::InstallInterrupted => "Package::InstallInterrupted",
         }
     }
 }
@@ -506,9 +701,12 @@ impl TryFrom<&str> for PackageActionType {
         Ok(match value {
             "Package::Install" => PackageActionType::Install,
             "Package::Uninstall" => PackageActionType::Uninstall,
+            "Package::Upgrade" => PackageActionType::Upgrade,
             "Package::InstallWithError" => PackageActionType::InstallWithError,
             "Package::IsInstalled" => PackageActionType::IsInstalled,
             "Package::IsNotInstalled" => PackageActionType::IsNotInstalled,
+            "Package::Inspect" => PackageActionType::Inspect,
+            "Package::InstallInterrupted" => PackageActionType::InstallInterrupted,
             _ => return Err("Invalid Package action type"),
         })
     }
@@ -532,3 +730,222 @@ impl TryFrom<&str> for DnsActionType {
         })
     }
 }
+
+impl<'a> From<&'a SnapshotActionType> for &'a str {
+    fn from(value: &SnapshotActionType) -> &str {
+        match value {
+            SnapshotActionType::Take => "Snapshot::Take",
+            SnapshotActionType::Compare => "Snapshot::Compare",
+        }
+    }
+}
+impl TryFrom<&str> for SnapshotActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Snapshot::Take" => SnapshotActionType::Take,
+            "Snapshot::Compare" => SnapshotActionType::Compare,
+            _ => return Err("Invalid Snapshot action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a TraceActionType> for &'a str {
+    fn from(value: &TraceActionType) -> &str {
+        match value {
+            TraceActionType::Start => "Trace::Start",
+            TraceActionType::Stop => "Trace::Stop",
+        }
+    }
+}
+impl TryFrom<&str> for TraceActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Trace::Start" => TraceActionType::Start,
+            "Trace::Stop" => TraceActionType::Stop,
+            _ => return Err("Invalid Trace action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a AuditActionType> for &'a str {
+    fn from(value: &AuditActionType) -> &str {
+        match value {
+            AuditActionType::Permissions => "Audit::Permissions",
+            AuditActionType::ListeningPorts => "Audit::ListeningPorts",
+        }
+    }
+}
+impl TryFrom<&str> for AuditActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Audit::Permissions" => AuditActionType::Permissions,
+            "Audit::ListeningPorts" => AuditActionType::ListeningPorts,
+            _ => return Err("Invalid Audit action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a ProcessActionType> for &'a str {
+    fn from(value: &ProcessActionType) -> &str {
+        match value {
+            ProcessActionType::Kill => "Process::Kill",
+            ProcessActionType::Suspend => "Process::Suspend",
+            ProcessActionType::Resume => "Process::Resume",
+        }
+    }
+}
+impl TryFrom<&str> for ProcessActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Process::Kill" => ProcessActionType::Kill,
+            "Process::Suspend" => ProcessActionType::Suspend,
+            "Process::Resume" => ProcessActionType::Resume,
+            _ => return Err("Invalid Process action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a NetworkActionType> for &'a str {
+    fn from(value: &NetworkActionType) -> &str {
+        match value {
+            NetworkActionType::Degrade => "Network::Degrade",
+            NetworkActionType::Restore => "Network::Restore",
+        }
+    }
+}
+impl TryFrom<&str> for NetworkActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Network::Degrade" => NetworkActionType::Degrade,
+            "Network::Restore" => NetworkActionType::Restore,
+            _ => return Err("Invalid Network action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a FirewallActionType> for &'a str {
+    fn from(value: &FirewallActionType) -> &str {
+        match value {
+            FirewallActionType::Block => "Firewall::Block",
+            FirewallActionType::Unblock => "Firewall::Unblock",
+        }
+    }
+}
+impl TryFrom<&str> for FirewallActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Firewall::Block" => FirewallActionType::Block,
+            "Firewall::Unblock" => FirewallActionType::Unblock,
+            _ => return Err("Invalid Firewall action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a StressActionType> for &'a str {
+    fn from(value: &StressActionType) -> &str {
+        match value {
+            StressActionType::Cpu => "Stress::Cpu",
+            StressActionType::Memory => "Stress::Memory",
+            StressActionType::Io => "Stress::Io",
+            StressActionType::Stop => "Stress::Stop",
+        }
+    }
+}
+impl TryFrom<&str> for StressActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Stress::Cpu" => StressActionType::Cpu,
+            "Stress::Memory" => StressActionType::Memory,
+            "Stress::Io" => StressActionType::Io,
+            "Stress::Stop" => StressActionType::Stop,
+            _ => return Err("Invalid Stress action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a FilesystemActionType> for &'a str {
+    fn from(value: &FilesystemActionType) -> &str {
+        match value {
+            FilesystemActionType::Fill => "Filesystem::Fill",
+            FilesystemActionType::ReadOnly => "Filesystem::ReadOnly",
+            FilesystemActionType::Tmpfs => "Filesystem::Tmpfs",
+            FilesystemActionType::Release => "Filesystem::Release",
+        }
+    }
+}
+impl TryFrom<&str> for FilesystemActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Filesystem::Fill" => FilesystemActionType::Fill,
+            "Filesystem::ReadOnly" => FilesystemActionType::ReadOnly,
+            "Filesystem::Tmpfs" => FilesystemActionType::Tmpfs,
+            "Filesystem::Release" => FilesystemActionType::Release,
+            _ => return Err("Invalid Filesystem action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a TimeActionType> for &'a str {
+    fn from(value: &TimeActionType) -> &str {
+        match value {
+            TimeActionType::Shift => "Time::Shift",
+            TimeActionType::Freeze => "Time::Freeze",
+            TimeActionType::Restore => "Time::Restore",
+        }
+    }
+}
+impl TryFrom<&str> for TimeActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Time::Shift" => TimeActionType::Shift,
+            "Time::Freeze" => TimeActionType::Freeze,
+            "Time::Restore" => TimeActionType::Restore,
+            _ => return Err("Invalid Time action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a LimitsActionType> for &'a str {
+    fn from(value: &LimitsActionType) -> &str {
+        match value {
+            LimitsActionType::Apply => "Limits::Apply",
+            LimitsActionType::Reset => "Limits::Reset",
+        }
+    }
+}
+impl TryFrom<&str> for LimitsActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Limits::Apply" => LimitsActionType::Apply,
+            "Limits::Reset" => LimitsActionType::Reset,
+            _ => return Err("Invalid Limits action type"),
+        })
+    }
+}
+
+impl<'a> From<&'a ChaosActionType> for &'a str {
+    fn from(value: &ChaosActionType) -> &str {
+        match value {
+            ChaosActionType::Random => "Chaos::Random",
+        }
+    }
+}
+impl TryFrom<&str> for ChaosActionType {
+    type Error = &'static str;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "Chaos::Random" => ChaosActionType::Random,
+            _ => return Err("Invalid Chaos action type"),
+        })
+    }
+}
@@ -8,9 +8,150 @@ pub const INSTALL_ERROR : &str = "install_error";
 
 pub const INSTALL_PARAMETERS : &str = "install_parameters";
 
+/// How install parameters reach Linux packages: "env" (default) or "debconf"
+pub const INSTALL_PARAMETERS_MODE : &str = "install_parameters_mode";
+
+/// Version that must be installed when checking the application
+pub const EXPECTED_VERSION : &str = "expected_version";
+
+/// All files and folders used by the application
+pub const APPLICATION_FOLDERS : &str = "application_folders";
+
+/// Signal used to interrupt the installer: SIGKILL (default) or SIGTERM
+pub const INTERRUPT_SIGNAL : &str = "interrupt_signal";
+/// Time to wait before interrupting the installer
+pub const INTERRUPT_DELAY : &str = "interrupt_delay";
+/// The installer is interrupted when this file appears
+pub const INTERRUPT_FILE : &str = "interrupt_file";
+
 pub const APP_SERVICE_NAME : &str = "service_name";
 
 pub const TASK_RETRIES : &str = "task_retries";
 
 pub const SERVER_DOMAIN : &str = "server_domain";
-pub const SERVER_IP : &str = "server_ip";
\ No newline at end of file
+pub const SERVER_IP : &str = "server_ip";
+
+/// Name of the system snapshot to take or to compare against
+pub const SNAPSHOT_NAME : &str = "snapshot_name";
+/// Folders whose file tree is recorded in a snapshot
+pub const SNAPSHOT_ROOTS : &str = "snapshot_roots";
+/// Path prefixes excluded from the snapshot file tree
+pub const SNAPSHOT_IGNORE : &str = "snapshot_ignore";
+
+/// Name of the filesystem trace to start or stop
+pub const TRACE_NAME : &str = "trace_name";
+/// Folders watched by a filesystem trace
+pub const TRACE_ROOTS : &str = "trace_roots";
+
+/// Octal modes allowed in the application folders
+pub const AUDIT_ALLOWED_MODES : &str = "audit_allowed_modes";
+/// Users allowed to own the files of the application folders
+pub const AUDIT_ALLOWED_OWNERS : &str = "audit_allowed_owners";
+/// Files that must not have the setuid/setgid bits
+pub const AUDIT_FORBIDDEN_SETUID : &str = "audit_forbidden_setuid";
+/// Executable path of the application process whose listening ports are audited
+pub const AUDIT_PROCESS_NAME : &str = "audit_process_name";
+/// Ports the application is allowed to listen on
+pub const AUDIT_ALLOWED_PORTS : &str = "audit_allowed_ports";
+
+/// Executable path of the process targeted by the process actions
+pub const PROCESS_EXECUTABLE : &str = "process_executable";
+/// Signal sent by Process::Kill
+pub const PROCESS_SIGNAL : &str = "process_signal";
+/// Task data: PIDs affected by a process action
+pub const AFFECTED_PIDS : &str = "affected_pids";
+/// Task data: PID of the service after recovering from a crash
+pub const RECOVERED_PID : &str = "recovered_pid";
+/// Task data: milliseconds the service took to recover from a crash
+pub const RECOVERY_TIME : &str = "recovery_time_ms";
+/// Task data: restarts of the service counted by systemd
+pub const SERVICE_RESTARTS : &str = "service_restarts";
+
+/// Network interface where the network faults are applied
+pub const NETWORK_INTERFACE : &str = "network_interface";
+/// Only traffic to this IP address or network is degraded
+pub const NETWORK_TARGET_HOST : &str = "network_target_host";
+/// Latency added to the network traffic
+pub const NETWORK_DELAY : &str = "network_delay";
+/// Variation of the added latency
+pub const NETWORK_JITTER : &str = "network_jitter";
+/// Percentage of dropped packets
+pub const NETWORK_LOSS : &str = "network_loss";
+/// Percentage of corrupted packets
+pub const NETWORK_CORRUPT : &str = "network_corrupt";
+/// Bandwidth limit of the network traffic
+pub const NETWORK_RATE : &str = "network_rate";
+
+/// IP address or network blocked by the firewall actions
+pub const FIREWALL_HOST : &str = "firewall_host";
+/// Destination port blocked by the firewall actions
+pub const FIREWALL_PORT : &str = "firewall_port";
+/// Protocol of the blocked port: tcp or udp
+pub const FIREWALL_PROTOCOL : &str = "firewall_protocol";
+/// What to do with the blocked traffic: drop or reject
+pub const FIREWALL_ACTION : &str = "firewall_action";
+
+/// Time a stress action generates load
+pub const STRESS_DURATION : &str = "stress_duration";
+/// Load generator threads of a stress action
+pub const STRESS_THREADS : &str = "stress_threads";
+/// Megabytes allocated by Stress::Memory
+pub const STRESS_MEMORY_MB : &str = "stress_memory_mb";
+/// Size of the file rewritten by Stress::Io
+pub const STRESS_IO_SIZE_MB : &str = "stress_io_size_mb";
+
+/// Folder targeted by the filesystem actions
+pub const FILESYSTEM_PATH : &str = "filesystem_path";
+/// Bytes left free in the volume by Filesystem::Fill
+pub const FILESYSTEM_FREE_BYTES : &str = "filesystem_free_bytes";
+/// Size of the tmpfs mounted by Filesystem::Tmpfs
+pub const FILESYSTEM_TMPFS_SIZE : &str = "filesystem_tmpfs_size";
+/// User that must see the volume full after Filesystem::Fill: root or user
+pub const FILESYSTEM_FILL_FOR : &str = "filesystem_fill_for";
+
+/// Offset applied to the system clock by Time::Shift
+pub const TIME_OFFSET : &str = "time_offset";
+/// Date where Time::Freeze stops the clock of the applications started under libfaketime
+pub const TIME_FREEZE : &str = "time_freeze";
+/// Timestamp file read by libfaketime
+pub const TIME_FAKETIME_FILE : &str = "time_faketime_file";
+/// Task data: milliseconds the system clock is moved from the real time
+pub const TIME_SKEW : &str = "time_skew_ms";
+
+/// MemoryMax of the service under test
+pub const LIMITS_MEMORY_MAX : &str = "limits_memory_max";
+/// CPUQuota of the service under test
+pub const LIMITS_CPU_QUOTA : &str = "limits_cpu_quota";
+/// TasksMax of the service under test
+pub const LIMITS_TASKS_MAX : &str = "limits_tasks_max";
+/// LimitNOFILE of the service under test
+pub const LIMITS_NOFILE : &str = "limits_nofile";
+
+/// Duration of the Wait action
+pub const WAIT_DURATION : &str = "wait_duration";
+
+/// Actions injected in background while the phase ran
+pub const DURING_INJECTIONS : &str = "during_injections";
+
+/// Time the Soak phase runs
+pub const SOAK_DURATION : &str = "soak_duration";
+/// Time between two rounds of Soak checks
+pub const SOAK_INTERVAL : &str = "soak_interval";
+/// Checks of each Soak round
+pub const SOAK_CHECKS : &str = "soak_checks";
+/// Failed checks that fail the Soak phase
+pub const SOAK_MAX_VIOLATIONS : &str = "soak_max_violations";
+/// Time series of the Soak check results
+pub const SOAK_RESULTS : &str = "soak_results";
+
+/// Prefix of the statistics of each metric when the metrics are stopped. Ex: metric_stats_cpu
+pub const METRIC_STATS : &str = "metric_stats";
+
+/// Max RAM growth in bytes per hour allowed by Metrics::AssertNoLeak
+pub const METRIC_LEAK_MAX_GROWTH : &str = "metric_leak_max_growth";
+/// Samples ignored at the start of the metrics by Metrics::AssertNoLeak
+pub const METRIC_LEAK_WARMUP : &str = "metric_leak_warmup";
+/// Min R² of the RAM trend to consider it a leak
+pub const METRIC_LEAK_MIN_CONFIDENCE : &str = "metric_leak_min_confidence";
+/// RAM trend calculated by Metrics::AssertNoLeak
+pub const METRIC_RAM_TREND : &str = "metric_ram_trend";
@@ -0,0 +1,120 @@
+use std::{net::IpAddr, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::{TestParameter, TestParameters}};
+
+use super::{get_duration_field, get_string_field, names::*};
+
+/// Network fault parameters:
+/// network_interface: Interface where the faults are applied. Ex: eth0, lo
+/// network_target_host: Only traffic to this IP address or network (Ex: 10.0.0.0/24) is degraded. All traffic if not present
+/// network_delay: Added latency. Ex: 100ms
+/// network_jitter: Latency variation. Requires network_delay
+/// network_loss: Percentage of dropped packets
+/// network_corrupt: Percentage of corrupted packets
+/// network_rate: Bandwidth limit. Ex: 1mbit, 512kbit
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct NetworkParameters {
+    pub interface: String,
+    pub target_host: Option<String>,
+    pub delay: Option<Duration>,
+    pub jitter: Option<Duration>,
+    pub loss: Option<f64>,
+    pub corrupt: Option<f64>,
+    pub rate: Option<String>,
+}
+
+impl NetworkParameters {
+    /// At least one fault is configured
+    pub fn has_faults(&self) -> bool {
+        self.delay.is_some() || self.loss.is_some() || self.corrupt.is_some() || self.rate.is_some()
+    }
+
+    /// Target host is an IPv6 address or network
+    pub fn target_is_ipv6(&self) -> bool {
+        self.target_host.as_ref().map(|v| v.contains(':')).unwrap_or(false)
+    }
+}
+
+impl TryFrom<&TestParameters> for NetworkParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let interface = get_string_field(params, NETWORK_INTERFACE)?;
+        let target_host = get_string_field(params, NETWORK_TARGET_HOST).ok();
+        if let Some(host) = &target_host {
+            let (address, prefix) = match host.split_once('/') {
+                Some((address, prefix)) => (address, Some(prefix)),
+                None => (host.as_str(), None),
+            };
+            if IpAddr::from_str(address).is_err() || prefix.map(|v| v.parse::<u8>().is_err()).unwrap_or(false) {
+                return Err(ChaosError::Other(format!("Invalid target host {}, expected an IP address or network", host)));
+            }
+        }
+        let delay = get_duration_field(params, NETWORK_DELAY).ok();
+        let jitter = get_duration_field(params, NETWORK_JITTER).ok();
+        if jitter.is_some() && delay.is_none() {
+            return Err(ChaosError::Other(format!("Parameter {:?} requires {:?}", NETWORK_JITTER, NETWORK_DELAY)));
+        }
+        let loss = get_percentage_field(params, NETWORK_LOSS)?;
+        let corrupt = get_percentage_field(params, NETWORK_CORRUPT)?;
+        let rate = get_string_field(params, NETWORK_RATE).ok();
+        Ok(Self {
+            interface,
+            target_host,
+            delay,
+            jitter,
+            loss,
+            corrupt,
+            rate,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for NetworkParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+/// Percentage as an integer or a float between 0 and 100
+fn get_percentage_field(params: &TestParameters, field: &str) -> Result<Option<f64>, ChaosError> {
+    let value = match params.get(field) {
+        Some(TestParameter::F64(v)) => *v,
+        Some(TestParameter::U64(v)) => *v as f64,
+        Some(TestParameter::I64(v)) => *v as f64,
+        Some(_) => return Err(ChaosError::Other(format!("Invalid parameter {:?}, expected a percentage", field))),
+        None => return Ok(None),
+    };
+    if !(0.0..=100.0).contains(&value) {
+        return Err(ChaosError::Other(format!("Invalid parameter {:?}, {} is not a percentage", field, value)));
+    }
+    Ok(Some(value))
+}
+
+#[test]
+fn should_parse_network_parameters() {
+    let mut params = TestParameters::new();
+    params.insert(NETWORK_INTERFACE, TestParameter::Text("lo".into()));
+    params.insert(NETWORK_DELAY, TestParameter::Text("100ms".into()));
+    params.insert(NETWORK_JITTER, TestParameter::Text("10ms".into()));
+    params.insert(NETWORK_LOSS, TestParameter::U64(5));
+    params.insert(NETWORK_TARGET_HOST, TestParameter::Text("10.0.0.0/24".into()));
+    let network: NetworkParameters = (&params).try_into().unwrap();
+    assert_eq!(Some(Duration::from_millis(100)), network.delay);
+    assert_eq!(Some(5.0), network.loss);
+    assert!(network.has_faults());
+    assert!(!network.target_is_ipv6());
+
+    params.insert(NETWORK_LOSS, TestParameter::F64(120.0));
+    assert!(NetworkParameters::try_from(&params).is_err());
+    params.insert(NETWORK_LOSS, TestParameter::F64(0.5));
+    params.insert(NETWORK_TARGET_HOST, TestParameter::Text("backend.local".into()));
+    assert!(NetworkParameters::try_from(&params).is_err());
+
+    let mut params = TestParameters::new();
+    params.insert(NETWORK_INTERFACE, TestParameter::Text("lo".into()));
+    params.insert(NETWORK_JITTER, TestParameter::Text("10ms".into()));
+    assert!(NetworkParameters::try_from(&params).is_err());
+}
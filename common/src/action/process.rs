@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, names::*};
+
+/// Process chaos parameters:
+/// process_executable: Executable path of the target process
+/// service_name: Service of the target process, used when process_executable is not present
+/// process_signal: Signal sent by Process::Kill. SIGKILL by default
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ProcessParameters {
+    pub executable: Option<String>,
+    pub service_name: Option<String>,
+    pub signal: String,
+}
+
+impl TryFrom<&TestParameters> for ProcessParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let executable = get_string_field(params, PROCESS_EXECUTABLE).ok();
+        let service_name = get_string_field(params, APP_SERVICE_NAME).ok();
+        if executable.is_none() && service_name.is_none() {
+            return Err(ChaosError::Other(format!("Parameter {:?} or {:?} not found", PROCESS_EXECUTABLE, APP_SERVICE_NAME)));
+        }
+        let signal = get_string_field(params, PROCESS_SIGNAL).unwrap_or_else(|_| "SIGKILL".to_string());
+        Ok(Self {
+            executable,
+            service_name,
+            signal,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for ProcessParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
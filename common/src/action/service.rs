@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{parameters::TestParameters, err::ChaosError};
 
-use super::{get_string_field, get_timeout_field, names::*};
+use super::{get_string_field, get_timeout_field, get_u64_field, names::*};
 
 /// Service parameters to be executed
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -25,4 +25,41 @@ impl TryFrom<&TestParameters> for ServiceCommand {
             timeout
         })
     }
-}
\ No newline at end of file
+}
+
+/// Resource limits of the service applied with a systemd drop-in:
+/// limits_memory_max: MemoryMax. Ex: 512M
+/// limits_cpu_quota: CPUQuota. Ex: 50%
+/// limits_tasks_max: TasksMax
+/// limits_nofile: LimitNOFILE
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ServiceLimits {
+    pub service : ServiceCommand,
+    pub memory_max : Option<String>,
+    pub cpu_quota : Option<String>,
+    pub tasks_max : Option<u64>,
+    pub nofile : Option<u64>
+}
+
+impl TryFrom<&TestParameters> for ServiceLimits {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let service = params.try_into()?;
+        let memory_max = get_string_field(params, LIMITS_MEMORY_MAX).ok();
+        let cpu_quota = get_string_field(params, LIMITS_CPU_QUOTA).ok();
+        if let Some(quota) = &cpu_quota {
+            if !quota.ends_with('%') || quota.trim_end_matches('%').parse::<u32>().is_err() {
+                return Err(ChaosError::Other(format!("Invalid CPU quota {}. Ex: 50%", quota)))
+            }
+        }
+        let tasks_max = get_u64_field(params, LIMITS_TASKS_MAX).ok();
+        let nofile = get_u64_field(params, LIMITS_NOFILE).ok();
+        Ok(ServiceLimits {
+            service,
+            memory_max,
+            cpu_quota,
+            tasks_max,
+            nofile
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, get_vec_string_field, names::*};
+
+/// Snapshot parameters:
+/// snapshot_name: Name used to store the snapshot and to compare against it. "default" if not present
+/// snapshot_roots: Folders whose file tree is recorded. Platform defaults if not present
+/// snapshot_ignore: Path prefixes excluded from the file tree
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SnapshotParameters {
+    pub name: String,
+    pub roots: Vec<String>,
+    pub ignore: Vec<String>,
+}
+
+impl TryFrom<&TestParameters> for SnapshotParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let name = get_string_field(params, SNAPSHOT_NAME).unwrap_or_else(|_| "default".to_string());
+        let roots = get_vec_string_field(params, SNAPSHOT_ROOTS).unwrap_or_default();
+        let ignore = get_vec_string_field(params, SNAPSHOT_IGNORE).unwrap_or_default();
+        Ok(Self {
+            name,
+            roots,
+            ignore,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for SnapshotParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+/// Entry of the file tree
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub size: u64,
+    pub mode: u32,
+    pub is_dir: bool,
+}
+
+/// State of the system relevant to detect leftovers of an application
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SystemSnapshot {
+    pub files: BTreeMap<String, SnapshotFile>,
+    pub packages: BTreeSet<String>,
+    pub units: BTreeSet<String>,
+    pub users: BTreeSet<String>,
+    pub groups: BTreeSet<String>,
+    pub cron: BTreeSet<String>,
+    pub hosts: BTreeSet<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSectionDiff {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub changed: Vec<String>,
+}
+
+/// Differences between two snapshots of the same system
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    pub files: SnapshotSectionDiff,
+    pub packages: SnapshotSectionDiff,
+    pub units: SnapshotSectionDiff,
+    pub users: SnapshotSectionDiff,
+    pub groups: SnapshotSectionDiff,
+    pub cron: SnapshotSectionDiff,
+    pub hosts: SnapshotSectionDiff,
+}
+
+impl SnapshotSectionDiff {
+    fn from_sets(before: &BTreeSet<String>, after: &BTreeSet<String>) -> Self {
+        Self {
+            added: after.difference(before).cloned().collect(),
+            removed: before.difference(after).cloned().collect(),
+            changed: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.sections().iter().all(|(_, section)| section.is_empty())
+    }
+
+    pub fn sections(&self) -> [(&'static str, &SnapshotSectionDiff); 7] {
+        [
+            ("files", &self.files),
+            ("packages", &self.packages),
+            ("units", &self.units),
+            ("users", &self.users),
+            ("groups", &self.groups),
+            ("cron", &self.cron),
+            ("hosts", &self.hosts),
+        ]
+    }
+
+    /// One line resume of the differences. Ex: files +2 -0 ~1, users +1 -0 ~0
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        for (name, section) in self.sections() {
+            if section.is_empty() {
+                continue;
+            }
+            parts.push(format!("{} +{} -{} ~{}", name, section.added.len(), section.removed.len(), section.changed.len()));
+        }
+        parts.join(", ")
+    }
+}
+
+impl SystemSnapshot {
+    /// Differences from this snapshot (before) to another one (after)
+    pub fn compare(&self, after: &SystemSnapshot) -> SnapshotDiff {
+        let mut files = SnapshotSectionDiff::default();
+        for (path, entry) in &after.files {
+            match self.files.get(path) {
+                Some(previous) => {
+                    let changed = previous.is_dir != entry.is_dir
+                        || previous.mode != entry.mode
+                        || (!entry.is_dir && previous.size != entry.size);
+                    if changed {
+                        files.changed.push(path.clone());
+                    }
+                }
+                None => files.added.push(path.clone()),
+            }
+        }
+        for path in self.files.keys() {
+            if !after.files.contains_key(path) {
+                files.removed.push(path.clone());
+            }
+        }
+        SnapshotDiff {
+            files,
+            packages: SnapshotSectionDiff::from_sets(&self.packages, &after.packages),
+            units: SnapshotSectionDiff::from_sets(&self.units, &after.units),
+            users: SnapshotSectionDiff::from_sets(&self.users, &after.users),
+            groups: SnapshotSectionDiff::from_sets(&self.groups, &after.groups),
+            cron: SnapshotSectionDiff::from_sets(&self.cron, &after.cron),
+            hosts: SnapshotSectionDiff::from_sets(&self.hosts, &after.hosts),
+        }
+    }
+}
+
+#[test]
+fn should_compare_snapshots() {
+    let mut before = SystemSnapshot::default();
+    before.files.insert("/etc/app.conf".into(), SnapshotFile { size: 10, mode: 0o644, is_dir: false });
+    before.files.insert("/etc/removed.conf".into(), SnapshotFile { size: 10, mode: 0o644, is_dir: false });
+    before.files.insert("/var/lib/app".into(), SnapshotFile { size: 4096, mode: 0o755, is_dir: true });
+    before.users.insert("root".into());
+    let mut after = before.clone();
+    assert!(before.compare(&after).is_empty());
+
+    after.files.insert("/etc/app.conf".into(), SnapshotFile { size: 20, mode: 0o644, is_dir: false });
+    after.files.insert("/var/lib/app".into(), SnapshotFile { size: 8192, mode: 0o755, is_dir: true });
+    after.files.remove("/etc/removed.conf");
+    after.files.insert("/opt/app/leftover".into(), SnapshotFile { size: 1, mode: 0o600, is_dir: false });
+    after.users.insert("app".into());
+    let diff = before.compare(&after);
+    assert_eq!(vec!["/opt/app/leftover".to_string()], diff.files.added);
+    assert_eq!(vec!["/etc/removed.conf".to_string()], diff.files.removed);
+    assert_eq!(vec!["/etc/app.conf".to_string()], diff.files.changed);
+    assert_eq!(vec!["app".to_string()], diff.users.added);
+    assert_eq!("files +1 -1 ~1, users +1 -0 ~0", diff.summary());
+}
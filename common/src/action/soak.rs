@@ -0,0 +1,141 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::{TestParameter, TestParameters}};
+
+use super::{get_duration_field, get_u64_field, get_vec_field, names::*, TestActionType};
+
+/// Soak parameters:
+/// soak_duration: Time the phase runs
+/// soak_interval: Time between two rounds of checks. 30s if not present
+/// soak_checks: Checks of each round. An action name (Service::IsRunning), {port: 8443} or {port: "host:8443"}, {log_absent: {file, pattern}}
+/// soak_max_violations: Failed checks that fail the phase. 1 (the first violation) if not present
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SoakParameters {
+    pub duration : Duration,
+    pub interval : Duration,
+    pub checks : Vec<SoakCheck>,
+    pub max_violations : u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SoakCheck {
+    /// The action must succeed
+    Action(TestActionType),
+    /// A TCP connection to the port must succeed
+    Port { host : String, port : u16 },
+    /// The lines appended to the file during the soak must not contain the pattern
+    LogAbsent { file : String, pattern : String },
+}
+
+impl SoakCheck {
+    /// Name of the check in the results
+    pub fn name(&self) -> String {
+        match self {
+            SoakCheck::Action(action) => <&str>::from(action).to_string(),
+            SoakCheck::Port { host, port } => format!("Port {}:{}", host, port),
+            SoakCheck::LogAbsent { file, pattern } => format!("LogAbsent {} {:?}", file, pattern),
+        }
+    }
+}
+
+impl TryFrom<&TestParameter> for SoakCheck {
+    type Error = ChaosError;
+    fn try_from(value: &TestParameter) -> Result<Self, Self::Error> {
+        let check = match value {
+            TestParameter::Text(action) => return Ok(SoakCheck::Action(action.as_str().into())),
+            TestParameter::Obj(v) => v,
+            _ => return Err(ChaosError::Other(format!("Invalid soak check {:?}", value))),
+        };
+        if let Some(port) = check.get("port") {
+            return parse_port_check(port)
+        }
+        if let Some(TestParameter::Obj(log)) = check.get("log_absent") {
+            let file = get_text(log, "file")?;
+            let pattern = get_text(log, "pattern")?;
+            return Ok(SoakCheck::LogAbsent { file, pattern })
+        }
+        Err(ChaosError::Other(format!("Invalid soak check {:?}, expected an action, port or log_absent", value)))
+    }
+}
+
+fn parse_port_check(value : &TestParameter) -> Result<SoakCheck, ChaosError> {
+    let value = match value {
+        TestParameter::U64(v) => v.to_string(),
+        TestParameter::I64(v) => v.to_string(),
+        TestParameter::Text(v) => v.clone(),
+        _ => return Err(ChaosError::Other(format!("Invalid soak port {:?}", value))),
+    };
+    let (host, port) = match value.rsplit_once(':') {
+        Some((host, port)) => (host.trim_start_matches('[').trim_end_matches(']').to_string(), port),
+        None => ("127.0.0.1".to_string(), value.as_str()),
+    };
+    let port = port.trim().parse::<u16>().map_err(|_| ChaosError::Other(format!("Invalid soak port {}", value)))?;
+    Ok(SoakCheck::Port { host, port })
+}
+
+fn get_text(obj : &BTreeMap<String, TestParameter>, field : &str) -> Result<String, ChaosError> {
+    obj.get(field)
+        .ok_or_else(|| ChaosError::Other(format!("Parameter {:?} not found in log_absent", field)))?
+        .try_into()
+        .map_err(|_| ChaosError::Other(format!("Invalid parameter {:?}, expected String", field)))
+}
+
+impl TryFrom<&TestParameters> for SoakParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let duration = get_duration_field(params, SOAK_DURATION)?;
+        let interval = get_duration_field(params, SOAK_INTERVAL).unwrap_or(Duration::from_secs(30));
+        let mut checks = Vec::new();
+        for check in get_vec_field(params, SOAK_CHECKS)? {
+            checks.push((&check).try_into()?);
+        }
+        if checks.is_empty() {
+            return Err(ChaosError::Other(format!("Parameter {:?} without checks", SOAK_CHECKS)))
+        }
+        let max_violations = get_u64_field(params, SOAK_MAX_VIOLATIONS).unwrap_or(1).max(1);
+        Ok(Self { duration, interval, checks, max_violations })
+    }
+}
+
+impl TryFrom<TestParameters> for SoakParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[test]
+fn should_parse_soak_checks() {
+    use super::ServiceActionType;
+    let mut port = BTreeMap::new();
+    port.insert("port".to_string(), TestParameter::U64(8443));
+    let mut remote_port = BTreeMap::new();
+    remote_port.insert("port".to_string(), TestParameter::Text("[::1]:9090".into()));
+    let mut log = BTreeMap::new();
+    log.insert("file".to_string(), TestParameter::Text("/var/log/app.log".into()));
+    log.insert("pattern".to_string(), TestParameter::Text("panic".into()));
+    let mut log_absent = BTreeMap::new();
+    log_absent.insert("log_absent".to_string(), TestParameter::Obj(log));
+    let mut params = TestParameters::new();
+    params.insert(SOAK_DURATION, TestParameter::Text("10m".into()));
+    params.insert(SOAK_CHECKS, TestParameter::Vec(vec![
+        TestParameter::Text("Service::IsRunning".into()),
+        TestParameter::Obj(port),
+        TestParameter::Obj(remote_port),
+        TestParameter::Obj(log_absent),
+        TestParameter::Text("Service:IsRunning".into()),
+    ]));
+    let soak : SoakParameters = (&params).try_into().unwrap();
+    assert_eq!(Duration::from_secs(600), soak.duration);
+    assert_eq!(Duration::from_secs(30), soak.interval);
+    assert_eq!(1, soak.max_violations);
+    assert_eq!(SoakCheck::Action(TestActionType::Service(ServiceActionType::IsRunning)), soak.checks[0]);
+    assert_eq!(SoakCheck::Port { host : "127.0.0.1".into(), port : 8443 }, soak.checks[1]);
+    assert_eq!(SoakCheck::Port { host : "::1".into(), port : 9090 }, soak.checks[2]);
+    assert_eq!("LogAbsent /var/log/app.log \"panic\"", soak.checks[3].name());
+    assert_eq!(soak.checks[0], soak.checks[4]);
+    params.insert(SOAK_CHECKS, TestParameter::Vec(vec![TestParameter::U64(1)]));
+    assert!(SoakParameters::try_from(&params).is_err());
+}
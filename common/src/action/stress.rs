@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_duration_field, get_u64_field, names::*};
+
+/// Stress parameters:
+/// stress_duration: Time the load is generated. Until Stress::Stop or the end of the scene if not present
+/// stress_threads: Load generator threads. All the cores for Stress::Cpu and 1 for Stress::Io if not present
+/// stress_memory_mb: Megabytes allocated by Stress::Memory
+/// stress_io_size_mb: Size of the file rewritten by each Stress::Io thread. 64 MB by default
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StressParameters {
+    pub duration: Option<Duration>,
+    pub threads: Option<usize>,
+    pub memory_mb: u64,
+    pub io_size_mb: u64,
+}
+
+impl TryFrom<&TestParameters> for StressParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let duration = get_duration_field(params, STRESS_DURATION).ok();
+        let threads = get_u64_field(params, STRESS_THREADS).ok().map(|v| v as usize);
+        if threads == Some(0) {
+            return Err(ChaosError::Other(format!("Parameter {:?} must be greater than 0", STRESS_THREADS)));
+        }
+        let memory_mb = get_u64_field(params, STRESS_MEMORY_MB).unwrap_or_default();
+        let io_size_mb = get_u64_field(params, STRESS_IO_SIZE_MB).unwrap_or(64).max(1);
+        Ok(Self {
+            duration,
+            threads,
+            memory_mb,
+            io_size_mb,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for StressParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[test]
+fn should_parse_stress_parameters() {
+    use crate::parameters::TestParameter;
+    let mut params = TestParameters::new();
+    params.insert(STRESS_DURATION, TestParameter::Text("30s".into()));
+    let stress: StressParameters = (&params).try_into().unwrap();
+    assert_eq!(Some(Duration::from_secs(30)), stress.duration);
+    assert_eq!(None, stress.threads);
+    assert_eq!(64, stress.io_size_mb);
+    params.insert(STRESS_THREADS, TestParameter::U64(0));
+    assert!(StressParameters::try_from(&params).is_err());
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{common::string_to_duration, err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, names::*};
+
+/// Time parameters:
+/// time_offset: Time::Shift moves the system clock by this offset. Ex: 90d, -2h, +30m
+/// time_freeze: Time::Freeze stops the clock of the applications started under libfaketime at this date. Ex: 2030-01-01 00:00:00
+/// time_faketime_file: File read by libfaketime (FAKETIME_TIMESTAMP_FILE). faketime.rc in the agent workspace if not present
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TimeParameters {
+    /// Milliseconds
+    pub offset: Option<i64>,
+    pub freeze: Option<String>,
+    pub faketime_file: Option<String>,
+}
+
+impl TryFrom<&TestParameters> for TimeParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let offset = match get_string_field(params, TIME_OFFSET) {
+            Ok(v) => Some(parse_offset(&v).ok_or_else(|| ChaosError::Other(format!("Invalid time offset {}. Ex: 90d, -2h", v)))?),
+            Err(_) => None,
+        };
+        let freeze = get_string_field(params, TIME_FREEZE).ok();
+        if let Some(freeze) = &freeze {
+            if !is_faketime_date(freeze) {
+                return Err(ChaosError::Other(format!("Invalid freeze date {}. Format: YYYY-MM-DD hh:mm:ss", freeze)));
+            }
+        }
+        let faketime_file = get_string_field(params, TIME_FAKETIME_FILE).ok();
+        Ok(Self {
+            offset,
+            freeze,
+            faketime_file,
+        })
+    }
+}
+
+impl TryFrom<TestParameters> for TimeParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+/// Duration with an optional sign, in milliseconds
+fn parse_offset(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (sign, duration) = match value.strip_prefix('-') {
+        Some(v) => (-1, v),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    if duration.is_empty() || !duration.starts_with(|v: char| v.is_ascii_digit()) {
+        return None;
+    }
+    Some(sign * string_to_duration(duration)?.as_millis() as i64)
+}
+
+/// Absolute date format of libfaketime: YYYY-MM-DD hh:mm:ss
+fn is_faketime_date(value: &str) -> bool {
+    let format = "0000-00-00 00:00:00";
+    value.len() == format.len()
+        && value.chars().zip(format.chars()).all(|(v, f)| if f == '0' { v.is_ascii_digit() } else { v == f })
+}
+
+#[test]
+fn should_parse_time_parameters() {
+    assert_eq!(Some(90 * 86400 * 1000), parse_offset("90d"));
+    assert_eq!(Some(-2 * 3600 * 1000), parse_offset("-2h"));
+    assert_eq!(Some(30 * 60 * 1000), parse_offset("+30m"));
+    assert_eq!(None, parse_offset("-"));
+    assert!(is_faketime_date("2030-01-01 00:00:00"));
+    assert!(!is_faketime_date("2030-01-01T00:00:00"));
+    assert!(!is_faketime_date("01/01/2030"));
+}
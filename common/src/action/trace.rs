@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{err::ChaosError, parameters::TestParameters};
+
+use super::{get_string_field, get_vec_string_field, names::*};
+
+/// Trace parameters:
+/// trace_name: Identifies the trace between Start and Stop. "default" if not present
+/// trace_roots: Folders watched recursively. Platform defaults if not present
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TraceParameters {
+    pub name: String,
+    pub roots: Vec<String>,
+}
+
+impl TryFrom<&TestParameters> for TraceParameters {
+    type Error = ChaosError;
+    fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
+        let name = get_string_field(params, TRACE_NAME).unwrap_or_else(|_| "default".to_string());
+        let roots = get_vec_string_field(params, TRACE_ROOTS).unwrap_or_default();
+        Ok(Self { name, roots })
+    }
+}
+
+impl TryFrom<TestParameters> for TraceParameters {
+    type Error = ChaosError;
+    fn try_from(value: TestParameters) -> Result<Self, ChaosError> {
+        (&value).try_into()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceOperation {
+    Create,
+    Modify,
+    Delete,
+    /// Permissions, owner or timestamps changed
+    Attributes,
+    MovedFrom,
+    MovedTo,
+}
+
+/// Filesystem operation over a path
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceEvent {
+    pub path: String,
+    pub operation: TraceOperation,
+    /// Milliseconds since UNIX epoch
+    pub timestamp: i64,
+}
+
+/// Filesystem activity between Trace::Start and Trace::Stop
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TraceArtifact {
+    pub name: String,
+    pub roots: Vec<String>,
+    pub start: i64,
+    pub end: i64,
+    pub events: Vec<TraceEvent>,
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{err::ChaosError, parameters::TestParameters};
 
-use super::get_duration_field;
+use super::{get_duration_field, names::WAIT_DURATION};
 
 /// Required parameters:
 /// duration: Sleep duration
@@ -18,7 +18,7 @@ pub struct WaitParameters {
 impl TryFrom<&TestParameters> for WaitParameters {
     type Error = ChaosError;
     fn try_from(params: &TestParameters) -> Result<Self, ChaosError> {
-        let wait_duration = get_duration_field(params, "wait_duration")?;
+        let wait_duration = get_duration_field(params, WAIT_DURATION)?;
         Ok(Self {
             duration : wait_duration
         })
@@ -157,6 +157,8 @@ pub enum AgentRequest {
     NextTask(u64),
     /// Agent completes a task
     CompleteTask(AgentTaskResult),
+    /// Partial results of a task that is still running
+    TaskProgress(AgentTaskResult),
     #[default]
     HeartBeat
 }
@@ -56,7 +56,7 @@ impl<'de> Visitor<'de> for StrDurationVisitor {
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
         where
             E: serde::de::Error, {
-            string_to_duration(v).ok_or(serde::de::Error::custom("Not a valid Duration string. Format 30s, 30m"))
+            string_to_duration(v).ok_or(serde::de::Error::custom("Not a valid Duration string. Format 30s, 30m, 2d, 500ms"))
     }
 }
 
@@ -69,6 +69,9 @@ pub fn string_to_duration(v : &str) -> Option<Duration> {
         }
         return Some(Duration::from_secs(chr1.to_digit(10)? as u64))
     }
+    if let Some(millis) = v.strip_suffix("ms") {
+        return Some(Duration::from_millis(millis.trim().parse::<u64>().ok()?))
+    }
     let last_char = v.chars().next_back()?;
     let (modifier, duration) = if last_char.is_numeric() {
         (1, v.parse::<u64>().unwrap_or(1))
@@ -82,6 +85,7 @@ fn modifier_from_letter(letter : char) -> u64 {
         's' => 1,
         'm' => 60,
         'h' => 3600,
+        'd' => 86400,
         _ => 1
     }
 }
@@ -92,4 +96,6 @@ fn should_parse_duration() {
     assert_eq!(Duration::from_secs(30), string_to_duration("30").unwrap());
     assert_eq!(Duration::from_secs(60), string_to_duration("1m").unwrap());
     assert_eq!(Duration::from_secs(3600), string_to_duration("1h").unwrap());
+    assert_eq!(Duration::from_millis(250), string_to_duration("250ms").unwrap());
+    assert_eq!(Duration::from_secs(2 * 86400), string_to_duration("2d").unwrap());
 }
\ No newline at end of file
@@ -338,6 +338,8 @@ impl TryFrom<&TestParameter> for f64 {
     fn try_from(value: &TestParameter) -> Result<Self, Self::Error> {
         Ok(match value {
             TestParameter::F64(v) => *v,
+            TestParameter::U64(v) => *v as f64,
+            TestParameter::I64(v) => *v as f64,
             _ => return Err("Invalid numeric value"),
         })
     }
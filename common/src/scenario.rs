@@ -1,19 +1,105 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use serde::{Serialize, Deserialize};
 
-use crate::{action::{CustomAction, TestActionType}, common::*, parameters::ScenarioParameters, variables::ScenarioVariables};
+use crate::{action::{CustomAction, PackageActionType, TestActionType}, common::*, err::{ChaosError, ChaosResult}, parameters::{ScenarioParameters, TestParameter}, variables::{ScenarioVariables, TestVariables}};
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TestScene {
     pub name : String,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub description : String,
-    pub phases : Vec<TestActionType>,
+    pub phases : Vec<TestPhase>,
     #[serde(default = "default_timeout", deserialize_with = "deserialize_null_default")]
     pub timeout : Duration,
     #[serde(default = "default_timeout", deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
-    pub phase_timeout : Duration
+    pub phase_timeout : Duration,
+    /// Variables with a list of values. The scene is executed once for each combination
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub matrix : BTreeMap<String, Vec<TestParameter>>
+}
+
+/// Phase of a scene. Written as the action name or as a map with the actions repeated while it runs
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "PhaseDefinition")]
+pub struct TestPhase {
+    pub action : TestActionType,
+    /// Actions repeated in background until the phase ends
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub during : Vec<DuringAction>
+}
+
+/// Action repeated at an interval while a phase runs. Ex: restart the service every 30s during a Wait
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DuringAction {
+    pub action : TestActionType,
+    #[serde(deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
+    pub every : Duration
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PhaseDefinition {
+    Action(TestActionType),
+    Phase {
+        action : TestActionType,
+        #[serde(default, deserialize_with = "deserialize_null_default")]
+        during : Vec<DuringAction>
+    }
+}
+
+impl From<PhaseDefinition> for TestPhase {
+    fn from(value: PhaseDefinition) -> Self {
+        match value {
+            PhaseDefinition::Action(action) => Self { action, during : Vec::new() },
+            PhaseDefinition::Phase { action, during } => Self { action, during }
+        }
+    }
+}
+
+impl From<TestActionType> for TestPhase {
+    fn from(action: TestActionType) -> Self {
+        Self { action, during : Vec::new() }
+    }
+}
+
+impl TestScene {
+    /// All the combinations of the matrix variables. A scene without matrix has a single empty combination.
+    /// Fails if a variable has no values or a value is not a text, number or boolean
+    pub fn matrix_combinations(&self) -> ChaosResult<Vec<TestVariables>> {
+        let mut combinations = vec![TestVariables::default()];
+        for (name, values) in &self.matrix {
+            if values.is_empty() {
+                return Err(ChaosError::Other(format!("Matrix variable {} of scene {} has no values", name, self.name)))
+            }
+            let mut texts = Vec::with_capacity(values.len());
+            for value in values {
+                texts.push(match value {
+                    TestParameter::Null | TestParameter::Obj(_) | TestParameter::Vec(_) => return Err(ChaosError::Other(format!("Invalid value {:?} of matrix variable {} in scene {}, expected text, number or boolean", value, name, self.name))),
+                    _ => String::try_from(value).map_err(|e| ChaosError::Other(e.into()))?
+                });
+            }
+            let mut expanded = Vec::with_capacity(combinations.len() * texts.len());
+            for combination in &combinations {
+                for value in &texts {
+                    let mut combination = combination.clone();
+                    combination.insert(name, value.clone().into());
+                    expanded.push(combination);
+                }
+            }
+            combinations = expanded;
+        }
+        Ok(combinations)
+    }
+
+    /// Name of the scene for a given matrix combination
+    pub fn matrix_name(&self, combination : &TestVariables) -> String {
+        if combination.inner().is_empty() {
+            return self.name.clone()
+        }
+        let values : Vec<String> = combination.inner().iter().map(|(k, v)| format!("{}={}", k, TryInto::<&str>::try_into(v).unwrap_or_default())).collect();
+        format!("{} ({})", self.name, values.join(", "))
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -35,7 +121,161 @@ pub struct TestScenario {
     pub scene_preparation : ScenePreparation,
     /// List of required files to be download before the testing begins
     #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub files : Vec<String>
+    pub files : Vec<String>,
+    /// Generates a scene for each upgrade path between package versions
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub upgrade_paths : UpgradePaths,
+    /// Faults injected by the Chaos::Random phases
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub chaos : RandomChaos
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum UpgradePathMode {
+    /// Upgrade from each version to every newer version
+    #[default]
+    All,
+    /// Upgrade only to the next version
+    Adjacent
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PackageVersion {
+    pub version : String,
+    /// Installer of this version in the server workspace
+    pub installer : String
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UpgradePaths {
+    /// Package versions sorted from the oldest to the newest
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub versions : Vec<PackageVersion>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub paths : UpgradePathMode,
+    /// Actions that check the upgraded application. Package::IsInstalled by default
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub verify : Vec<TestActionType>,
+    #[serde(default = "default_timeout", deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
+    pub phase_timeout : Duration
+}
+
+impl UpgradePaths {
+    /// Pairs of version positions (from, to) to be tested
+    pub fn paths(&self) -> Vec<(usize, usize)> {
+        let mut ret = Vec::with_capacity(self.versions.len() * self.versions.len());
+        for from in 0..self.versions.len() {
+            for to in (from + 1)..self.versions.len() {
+                if self.paths == UpgradePathMode::Adjacent && to != from + 1 {
+                    break
+                }
+                ret.push((from, to));
+            }
+        }
+        ret
+    }
+
+    pub fn verify_actions(&self) -> Vec<TestActionType> {
+        if self.verify.is_empty() {
+            return vec![TestActionType::Package(PackageActionType::IsInstalled)]
+        }
+        self.verify.clone()
+    }
+}
+
+/// Fault that can be picked by Chaos::Random
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChaosFault {
+    pub action : TestActionType,
+    /// Relative probability of the fault
+    #[serde(default = "default_chaos_weight")]
+    pub weight : u32
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RandomChaos {
+    /// Seed of the fault sequence. Random if not present. The seed used is stored in the run
+    #[serde(default)]
+    pub seed : Option<u64>,
+    /// Time during which faults are injected
+    #[serde(default = "default_chaos_window", deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
+    pub window : Duration,
+    #[serde(default = "default_chaos_min_interval", deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
+    pub min_interval : Duration,
+    #[serde(default = "default_chaos_max_interval", deserialize_with = "deserialize_duration", serialize_with = "serialize_duration")]
+    pub max_interval : Duration,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub pool : Vec<ChaosFault>
+}
+
+impl Default for RandomChaos {
+    fn default() -> Self {
+        Self {
+            seed : None,
+            window : default_chaos_window(),
+            min_interval : default_chaos_min_interval(),
+            max_interval : default_chaos_max_interval(),
+            pool : Vec::new()
+        }
+    }
+}
+
+fn default_chaos_weight() -> u32 {
+    1
+}
+fn default_chaos_window() -> Duration {
+    Duration::from_secs(300)
+}
+fn default_chaos_min_interval() -> Duration {
+    Duration::from_secs(10)
+}
+fn default_chaos_max_interval() -> Duration {
+    Duration::from_secs(60)
+}
+
+impl RandomChaos {
+    /// Faults of a scene and the time to wait before each one. The same seed and scene always give the same sequence
+    pub fn schedule(&self, seed : u64, scene_id : u32) -> Vec<(Duration, TestActionType)> {
+        let total_weight : u64 = self.pool.iter().map(|v| v.weight as u64).sum();
+        let mut schedule = Vec::new();
+        if total_weight == 0 {
+            return schedule
+        }
+        let mut rng = SplitMix64(seed ^ (scene_id as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let min = self.min_interval.as_millis() as u64;
+        let max = (self.max_interval.as_millis() as u64).max(min);
+        let window = self.window.as_millis() as u64;
+        let mut elapsed = 0;
+        loop {
+            let interval = min + rng.next() % (max - min + 1);
+            if interval == 0 || elapsed + interval > window {
+                break
+            }
+            elapsed += interval;
+            let mut choice = rng.next() % total_weight;
+            for fault in &self.pool {
+                if choice < fault.weight as u64 {
+                    schedule.push((Duration::from_millis(interval), fault.action.clone()));
+                    break
+                }
+                choice -= fault.weight as u64;
+            }
+        }
+        schedule
+    }
+}
+
+/// Small and stable generator. The sequence of a seed must not change between versions
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -66,6 +306,7 @@ pub struct ScenePreparationActions {
 #[cfg(test)]
 mod tst {
     use super::*;
+    use crate::action::ServiceActionType;
 
     #[test]
     pub fn should_parse_basic_scenario() {
@@ -73,4 +314,92 @@ mod tst {
         let basic_scene : TestScenario = serde_yaml::from_str(&file_content).unwrap();
         assert_eq!(Duration::from_secs(10), basic_scene.scene_preparation.phase_timeout);
     }
+
+    #[test]
+    pub fn should_parse_phases_with_background_actions() {
+        let scene : TestScene = serde_yaml::from_str(r#"
+name: Soak
+phases:
+  - Package::Install
+  - action: Wait
+    during:
+      - action: Service::Restart
+        every: 30s
+  - Package::Uninstall
+"#).unwrap();
+        assert_eq!(3, scene.phases.len());
+        assert!(scene.phases[0].during.is_empty());
+        assert_eq!(TestActionType::Wait, scene.phases[1].action);
+        assert_eq!(Duration::from_secs(30), scene.phases[1].during[0].every);
+        let serialized = serde_json::to_string(&scene).unwrap();
+        let scene : TestScene = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(TestActionType::Service(ServiceActionType::Restart), scene.phases[1].during[0].action);
+    }
+
+    #[test]
+    pub fn should_expand_scene_matrix() {
+        let scene : TestScene = serde_yaml::from_str(r#"
+name: Install
+phases:
+  - Package::Install
+matrix:
+  app_version: ["1.0.0", "1.1.0", "1.2.0"]
+  arch: [x86, x64]
+"#).unwrap();
+        let combinations = scene.matrix_combinations().unwrap();
+        assert_eq!(6, combinations.len());
+        assert_eq!("Install (app_version=1.0.0, arch=x86)", scene.matrix_name(&combinations[0]));
+        assert_eq!("Install (app_version=1.2.0, arch=x64)", scene.matrix_name(&combinations[5]));
+        let scene = TestScene { name : "Install".into(), ..Default::default() };
+        let combinations = scene.matrix_combinations().unwrap();
+        assert_eq!(1, combinations.len());
+        assert_eq!("Install", scene.matrix_name(&combinations[0]));
+        for matrix in ["app_version: []", "app_version: [1.0.0, {version: 1.1.0}]", "arch: [[x86, x64]]"] {
+            let scene : TestScene = serde_yaml::from_str(&format!("name: Install\nphases: [Package::Install]\nmatrix:\n  {}\n", matrix)).unwrap();
+            assert!(scene.matrix_combinations().is_err(), "{}", matrix);
+        }
+    }
+
+    #[test]
+    pub fn should_generate_upgrade_paths() {
+        let mut upgrades : UpgradePaths = serde_yaml::from_str(r#"
+versions:
+  - version: 1.0.0
+    installer: app_1.0.0.deb
+  - version: 1.1.0
+    installer: app_1.1.0.deb
+  - version: 1.2.0
+    installer: app_1.2.0.deb
+"#).unwrap();
+        assert_eq!(vec![(0, 1), (0, 2), (1, 2)], upgrades.paths());
+        upgrades.paths = UpgradePathMode::Adjacent;
+        assert_eq!(vec![(0, 1), (1, 2)], upgrades.paths());
+        assert_eq!(vec![TestActionType::Package(PackageActionType::IsInstalled)], upgrades.verify_actions());
+    }
+
+    #[test]
+    pub fn should_reproduce_random_chaos_with_seed() {
+        let chaos : RandomChaos = serde_yaml::from_str(r#"
+window: 10m
+min_interval: 10s
+max_interval: 30s
+pool:
+  - action: Process::Kill
+    weight: 3
+  - action: Service::Restart
+"#).unwrap();
+        let schedule = chaos.schedule(42, 0);
+        assert_eq!(format!("{:?}", schedule), format!("{:?}", chaos.schedule(42, 0)));
+        assert_ne!(format!("{:?}", schedule), format!("{:?}", chaos.schedule(43, 0)));
+        assert_ne!(format!("{:?}", schedule), format!("{:?}", chaos.schedule(42, 1)));
+        let total : Duration = schedule.iter().map(|v| v.0).sum();
+        assert!(total <= Duration::from_secs(600));
+        assert!(schedule.len() >= 20);
+        assert!(schedule.iter().all(|(interval, _)| *interval >= Duration::from_secs(10) && *interval <= Duration::from_secs(30)));
+        let kills = schedule.iter().filter(|(_, action)| matches!(action, TestActionType::Process(_))).count();
+        assert!(kills > schedule.len() / 2);
+        let restarts = schedule.iter().filter(|(_, action)| *action == TestActionType::Service(ServiceActionType::Restart)).count();
+        assert_eq!(schedule.len(), kills + restarts);
+        assert!(RandomChaos::default().schedule(42, 0).is_empty());
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::{action::TestActionType, err::ChaosError, parameters::TestParameters};
+use crate::{action::TestActionType, err::ChaosError, parameters::TestParameters, scenario::DuringAction, variables::TestVariables};
 
 use serde::{Serialize, Deserialize};
 
@@ -12,6 +12,12 @@ pub struct AgentTask {
     pub action : TestActionType,
     pub parameters : TestParameters,
     pub retries : u32,
+    /// Scene variables that override the global ones. Ex: matrix values
+    #[serde(default)]
+    pub variables : TestVariables,
+    /// Actions repeated in background while the task runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub during : Vec<DuringAction>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -25,7 +31,10 @@ pub struct AgentTaskResult {
     pub action : TestActionType,
     pub retries : u32,
     pub parameters : TestParameters,
-    pub result : Result<(), ChaosError>
+    pub result : Result<(), ChaosError>,
+    /// Data produced by the action. Ex: PIDs affected by a process action
+    #[serde(default)]
+    pub data : TestParameters
 }
 
 impl From<AgentTask> for AgentTaskResult {
@@ -41,7 +50,8 @@ impl From<AgentTask> for AgentTaskResult {
             retries,
             limit : v.limit,
             parameters : v.parameters,
-            result : Ok(())
+            result : Ok(()),
+            data : TestParameters::new()
         }
     }
 }
\ No newline at end of file
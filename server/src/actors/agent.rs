@@ -4,7 +4,7 @@ use actix::{
     Actor, Addr, StreamHandler
 };
 use actix_web_actors::ws;
-use chaos_core::api::{agent::{AgentRequest, AgentResponse}, Log};
+use chaos_core::{api::{agent::{AgentRequest, AgentResponse}, Log}, parameters::{TestParameter, TestParameters}};
 
 use crate::{domains::connection::{AgentAppLog, AgentCompletionUpdate, AgentLog}, state::ServerState};
 
@@ -78,6 +78,14 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for AgentConnection {
                 });
                 self.state.services.set_task_as_executed(task);
             },
+            AgentRequest::TaskProgress(task) => {
+                let log = format!("Task {} ({}) in progress: {}\n", task.id, <&str>::from(&task.action), task_progress(&task.data));
+                self.write_log_to_file(&log);
+                self.addr.do_send(AgentLog(Log {
+                    agent : self.id.clone(),
+                    msg : log
+                }));
+            },
             AgentRequest::HeartBeat => {},
             AgentRequest::NextTask(hash) => {
                 
@@ -117,6 +125,17 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for AgentConnection {
     }
 }
 
+/// Last value of each data series of a running task
+fn task_progress(data : &TestParameters) -> String {
+    data.inner().iter().map(|(name, value)| {
+        let value = match value {
+            TestParameter::Vec(v) => v.last().unwrap_or(&TestParameter::Null),
+            _ => value
+        };
+        format!("{}={}", name, serde_json::to_string(value).unwrap_or_default())
+    }).collect::<Vec<String>>().join(", ")
+}
+
 fn process_agent_message(msg: &[u8]) -> Option<AgentRequest> {
     serde_json::from_slice(msg).ok()
 }
@@ -40,7 +40,8 @@ impl Handler<ServerTask> for ServerActuator {
             result,
             retries : task.retries,
             scene_id : task.scene_id,
-            parameters : TestParameters::default()
+            parameters : TestParameters::default(),
+            data : TestParameters::default()
         });
     }
 }
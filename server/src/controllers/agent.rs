@@ -89,7 +89,8 @@ fn agent_info(req : &HttpRequest) -> Option<ConnectAgent> {
 async fn upload_artifact(
     request : HttpRequest,
     stream : web::Payload,
-    file_name : Path<String>
+    file_name : Path<String>,
+    state : Data<ServerState>
 ) -> impl Responder {
     let headers = request.headers();
     let agent_id = match headers.get("Agent-ID") {
@@ -134,5 +135,8 @@ async fn upload_artifact(
             return HttpResponse::InternalServerError().finish()
         }
     };
+    if let Err(e) = state.services.add_artifact_for_agent(agent_id, file_name.as_str()) {
+        log::warn!("Cannot register artifact: {}", e);
+    }
     HttpResponse::Ok().finish()
 }
\ No newline at end of file
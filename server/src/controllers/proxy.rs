@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 
 use actix_web::{http::Uri, web::{self, Data}, HttpMessage, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
-use chaos_core::{action::{HttpActionType, TestActionType}, err::{ChaosError, ChaosResult}, tasks::AgentTaskResult};
+use chaos_core::{action::{HttpActionType, TestActionType}, err::{ChaosError, ChaosResult}, parameters::TestParameters, tasks::AgentTaskResult};
 use reqwest::{RequestBuilder, StatusCode};
 use rhai::{Engine, Scope};
 
@@ -81,7 +81,8 @@ async fn proxy_request_wrapper(
                     end : now_milliseconds(),
                     limit : task.limit,
                     parameters : task.parameters,
-                    result
+                    result,
+                    data : TestParameters::new()
                 });
             }
         }
@@ -102,7 +103,8 @@ async fn proxy_request_wrapper(
                         end : now_milliseconds(),
                         limit : task.limit,
                         parameters : task.parameters,
-                        result : Err(ChaosError::Other(e.to_string()))
+                        result : Err(ChaosError::Other(e.to_string())),
+                        data : TestParameters::new()
                     });
                 }
             }
@@ -132,7 +134,8 @@ async fn proxy_request_wrapper(
                     end : now_milliseconds(),
                     limit : task.limit,
                     parameters : task.parameters,
-                    result
+                    result,
+                    data : TestParameters::new()
                 });
                 return Ok(ret)
             }
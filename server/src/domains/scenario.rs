@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use chaos_core::{action::{names::TASK_RETRIES, TestActionType}, parameters::{TestParameters, REMOTE_SERVER}, scenario::{ScenePreparationActions, TestScenario, TestScene}, tasks::AgentTask};
+use chaos_core::{action::{names::{EXPECTED_VERSION, INSTALLER_LOCATION, TASK_RETRIES, WAIT_DURATION}, ChaosActionType, PackageActionType, TestActionType}, err::{ChaosError, ChaosResult}, parameters::{TestParameter, TestParameters, REMOTE_SERVER}, scenario::{PackageVersion, ScenePreparationActions, TestPhase, TestScenario, TestScene}, tasks::AgentTask, variables::TestVariables};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -9,18 +9,48 @@ pub struct CalculatedScenario {
     pub name : String,
     pub remote_server : Option<String>,
     pub scenes : BTreeMap<u32, String>,
+    /// Matrix combination used by each generated scene
+    #[serde(default)]
+    pub matrix : BTreeMap<u32, TestVariables>,
+    /// Versions (from, to) tested by each generated upgrade scene
+    #[serde(default)]
+    pub upgrades : BTreeMap<u32, (String, String)>,
     pub tasks : Vec<AgentTask>,
     pub scenario : TestScenario
 }
 
-impl From<&TestScenario> for CalculatedScenario {
-    fn from(test: &TestScenario) -> Self {
+impl TryFrom<&TestScenario> for CalculatedScenario {
+    type Error = ChaosError;
+    fn try_from(test: &TestScenario) -> ChaosResult<Self> {
+        let mut test = test.clone();
+        if test.chaos.seed.is_none() && uses_random_chaos(&test) {
+            // Stored in the scenario of the run so it can be reproduced
+            test.chaos.seed = Some(random_seed());
+        }
+        let test = &test;
         let remote_server : Option<String> = test.parameters.global.get(REMOTE_SERVER).map(|v|v.try_into().unwrap_or_default());
         let mut tasks = Vec::with_capacity(test.scenes.len() * 32);
         let mut scenes: BTreeMap<u32, String> = BTreeMap::new();
-        for (i, scene) in test.scenes.iter().enumerate() {
-            scenes.insert(i as u32, scene.name.clone());
-            scene_to_tasks(scene, i as u32, test, &mut tasks);
+        let mut matrix: BTreeMap<u32, TestVariables> = BTreeMap::new();
+        for scene in test.scenes.iter() {
+            for combination in scene.matrix_combinations()? {
+                let scene_id = scenes.len() as u32;
+                scenes.insert(scene_id, scene.matrix_name(&combination));
+                scene_to_tasks(scene, scene_id, &combination, &[], test, &mut tasks);
+                if !combination.inner().is_empty() {
+                    matrix.insert(scene_id, combination);
+                }
+            }
+        }
+        let mut upgrades: BTreeMap<u32, (String, String)> = BTreeMap::new();
+        for (from, to) in test.upgrade_paths.paths() {
+            let from = &test.upgrade_paths.versions[from];
+            let to = &test.upgrade_paths.versions[to];
+            let scene_id = scenes.len() as u32;
+            let (scene, phase_parameters) = upgrade_scene(test, from, to);
+            scenes.insert(scene_id, format!("Upgrade {} to {}", from.version, to.version));
+            scene_to_tasks(&scene, scene_id, &TestVariables::default(), &phase_parameters, test, &mut tasks);
+            upgrades.insert(scene_id, (from.version.clone(), to.version.clone()));
         }
 
         let default_retry = test.parameters.global.get(TASK_RETRIES).map(|v| v.try_into().unwrap_or(1u32)).unwrap_or(1u32);
@@ -31,42 +61,156 @@ impl From<&TestScenario> for CalculatedScenario {
                 default_retry
             };
             tasks.push(AgentTask {
-                scene_id : test.scenes.len() as u32 - 1,
+                scene_id : scenes.len() as u32 - 1,
                 action : action.clone(),
                 agent : String::new(),
                 id : tasks.len() as u32,
                 preparation : true,
                 limit : test.scene_preparation.phase_timeout.as_millis() as i64,
                 parameters : TestParameters::new(),
-                retries
+                retries,
+                variables : TestVariables::default(),
+                during : Vec::new()
             })
         }
-        Self {
+        Ok(Self {
             scenes,
+            matrix,
+            upgrades,
             scenario : test.clone(),
             name : test.name.to_string(),
             remote_server,
             tasks
-        }
+        })
     }
 }
 
-fn scene_to_tasks(scene : &TestScene, scene_i : u32, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
-    scene_preparation(&scenario.scene_preparation.before, scene_i, scene, scenario, tasks);
+/// Install the old version, install the new one over it, verify and uninstall
+fn upgrade_scene(scenario : &TestScenario, from : &PackageVersion, to : &PackageVersion) -> (TestScene, Vec<TestParameters>) {
+    let mut from_params = TestParameters::new();
+    from_params.insert(INSTALLER_LOCATION, from.installer.clone().into());
+    let mut to_params = TestParameters::new();
+    to_params.insert(INSTALLER_LOCATION, to.installer.clone().into());
+    let mut phases = vec![TestActionType::Package(PackageActionType::Install), TestActionType::Package(PackageActionType::Upgrade)];
+    let mut phase_parameters = vec![from_params, to_params.clone()];
+    let mut verify_params = to_params.clone();
+    verify_params.insert(EXPECTED_VERSION, to.version.clone().into());
+    for action in scenario.upgrade_paths.verify_actions() {
+        phases.push(action);
+        phase_parameters.push(verify_params.clone());
+    }
+    phases.push(TestActionType::Package(PackageActionType::Uninstall));
+    phase_parameters.push(to_params);
+    let scene = TestScene {
+        phases : phases.into_iter().map(TestPhase::from).collect(),
+        phase_timeout : scenario.upgrade_paths.phase_timeout,
+        ..Default::default()
+    };
+    (scene, phase_parameters)
+}
+
+/// phase_parameters: parameters of each phase that override the global ones
+fn scene_to_tasks(scene : &TestScene, scene_i : u32, variables : &TestVariables, phase_parameters : &[TestParameters], scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+    scene_preparation(&scenario.scene_preparation.before, scene_i, scene, variables, scenario, tasks);
     for (i, phase) in scene.phases.iter().enumerate() {
-        scene_preparation(&scenario.scene_preparation.before_phase, scene_i, scene, scenario, tasks);
+        scene_preparation(&scenario.scene_preparation.before_phase, scene_i, scene, variables, scenario, tasks);
         if i == scene.phases.len() - 1 {
-            scene_preparation(&scenario.scene_preparation.before_last, scene_i, scene, scenario, tasks);
+            scene_preparation(&scenario.scene_preparation.before_last, scene_i, scene, variables, scenario, tasks);
+        }
+        let parameters = phase_parameters.get(i).cloned().unwrap_or_default();
+        if let TestActionType::Chaos(ChaosActionType::Random) = phase.action {
+            chaos_to_tasks(scene_i, scene, variables, scenario, tasks);
+        } else {
+            phase_to_tasks(&phase.action, scene_i, scene, variables, parameters, scenario, tasks);
+            if let Some(task) = tasks.last_mut() {
+                task.during = phase.during.clone();
+            }
         }
-        phase_to_tasks(phase, scene_i, scene, scenario, tasks);
         if i == 0 {
-            scene_preparation(&scenario.scene_preparation.after_first, scene_i, scene, scenario, tasks);
+            scene_preparation(&scenario.scene_preparation.after_first, scene_i, scene, variables, scenario, tasks);
+        }
+        scene_preparation(&scenario.scene_preparation.after_phase, scene_i, scene, variables, scenario, tasks);
+    }
+    undo_faults(scene, scene_i, variables, phase_parameters, scenario, tasks);
+    scene_preparation(&scenario.scene_preparation.after, scene_i, scene, variables, scenario, tasks);
+}
+
+/// Waits and faults of a Chaos::Random phase. The same seed gives the same tasks
+fn chaos_to_tasks(scene_id : u32, scene : &TestScene, variables : &TestVariables, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+    let seed = scenario.chaos.seed.unwrap_or_default();
+    for (interval, action) in scenario.chaos.schedule(seed, scene_id) {
+        let mut parameters = TestParameters::new();
+        parameters.insert(WAIT_DURATION, TestParameter::Text(format!("{}ms", interval.as_millis())));
+        tasks.push(AgentTask {
+            scene_id,
+            action : TestActionType::Wait,
+            agent : String::new(),
+            id : tasks.len() as u32,
+            preparation : false,
+            limit : (interval + scene.phase_timeout).as_millis() as i64,
+            parameters,
+            retries : u32::MAX,
+            variables : variables.clone(),
+            during : Vec::new()
+        });
+        phase_to_tasks(&action, scene_id, scene, variables, TestParameters::new(), scenario, tasks);
+    }
+}
+
+fn uses_random_chaos(scenario : &TestScenario) -> bool {
+    scenario.scenes.iter().any(|scene| scene.phases.iter().any(|v| v.action == TestActionType::Chaos(ChaosActionType::Random)))
+}
+
+fn random_seed() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|v| v.as_nanos() as u64).unwrap_or_default()
+}
+
+/// Removes the faults still applied at the end of the scene, the last one first. Tasks run even if a previous phase fails
+fn undo_faults(scene : &TestScene, scene_i : u32, variables : &TestVariables, phase_parameters : &[TestParameters], scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+    for (i, phase) in scene.phases.iter().enumerate().rev() {
+        if let TestActionType::Chaos(ChaosActionType::Random) = phase.action {
+            undo_random_chaos(scene, scene_i, variables, scenario, tasks);
+            continue
+        }
+        let undo = match resolve_custom_action(&phase.action, scenario).undo_action() {
+            Some(v) => v,
+            None => continue
+        };
+        if scene.phases[i + 1..].iter().any(|v| resolve_custom_action(&v.action, scenario) == &undo) {
+            continue
+        }
+        let parameters = phase_parameters.get(i).cloned().unwrap_or_default();
+        phase_to_tasks(&undo, scene_i, scene, variables, parameters, scenario, tasks);
+    }
+}
+
+/// Undoes each kind of fault of the chaos pool once. The undo actions clean every fault of their kind
+fn undo_random_chaos(scene : &TestScene, scene_i : u32, variables : &TestVariables, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+    let mut undone : Vec<TestActionType> = Vec::new();
+    for fault in &scenario.chaos.pool {
+        let undo = match resolve_custom_action(&fault.action, scenario).undo_action() {
+            Some(v) => v,
+            None => continue
+        };
+        if undone.contains(&undo) {
+            continue
+        }
+        phase_to_tasks(&undo, scene_i, scene, variables, TestParameters::new(), scenario, tasks);
+        undone.push(undo);
+    }
+}
+
+fn resolve_custom_action<'a>(action : &'a TestActionType, scenario : &'a TestScenario) -> &'a TestActionType {
+    if let TestActionType::Custom(c) = action {
+        for act in &scenario.actions {
+            if &act.name == c {
+                return &act.action
+            }
         }
-        scene_preparation(&scenario.scene_preparation.after_phase, scene_i, scene, scenario, tasks);
     }
-    scene_preparation(&scenario.scene_preparation.after, scene_i, scene, scenario, tasks);
+    action
 }
-fn phase_to_tasks(action : &TestActionType, scene_id : u32, scene : &TestScene, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+fn phase_to_tasks(action : &TestActionType, scene_id : u32, scene : &TestScene, variables : &TestVariables, parameters : TestParameters, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
     let retries = if action_is_wait(action, scenario) {
         u32::MAX
     }else {
@@ -79,12 +223,14 @@ fn phase_to_tasks(action : &TestActionType, scene_id : u32, scene : &TestScene,
         id : tasks.len() as u32,
         preparation : false,
         limit : scene.phase_timeout.as_millis() as i64,
-        parameters : TestParameters::new(),
-        retries
+        parameters,
+        retries,
+        variables : variables.clone(),
+        during : Vec::new()
     });
 }
 
-fn scene_preparation(preps : &ScenePreparationActions, scene_id : u32, scene : &TestScene, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
+fn scene_preparation(preps : &ScenePreparationActions, scene_id : u32, scene : &TestScene, variables : &TestVariables, scenario : &TestScenario, tasks : &mut Vec<AgentTask>) {
     let default_retry = scenario.parameters.global.get(TASK_RETRIES).map(|v| v.try_into().unwrap_or(1u32)).unwrap_or(1u32);
     for action in &preps.actions {
         let retries = if action_is_wait(action, scenario) {
@@ -100,7 +246,9 @@ fn scene_preparation(preps : &ScenePreparationActions, scene_id : u32, scene : &
             preparation : true,
             limit : scene.phase_timeout.as_millis() as i64,
             parameters : TestParameters::new(),
-            retries
+            retries,
+            variables : variables.clone(),
+            during : Vec::new()
         })
     }
 }
@@ -35,7 +35,10 @@ pub struct AgentSceneState {
     pub last_task : Option<u32>,
     pub results : BTreeMap<u32, AgentTaskResult>,
     #[serde(deserialize_with="deserialize_null_default")]
-    pub metric : BTreeMap<String , MetricsArtifact>
+    pub metric : BTreeMap<String , MetricsArtifact>,
+    /// Artifacts uploaded by the agent while executing each task
+    #[serde(default)]
+    pub artifacts : BTreeMap<u32, Vec<String>>
 }
 
 impl Database {
@@ -60,4 +63,14 @@ impl Database {
         entry.last_task = Some(task.id);
         entry.results.insert(task.id, task);
     }
+
+    /// Links the artifact to the task being executed by the agent
+    pub fn add_artifact(&mut self, agent : &str, name : &str) {
+        let entry = self.state.entry(agent.to_string()).or_default();
+        let task_id = entry.last_task.map(|v| v + 1).unwrap_or(0);
+        let artifacts = entry.artifacts.entry(task_id).or_default();
+        if !artifacts.iter().any(|v| v == name) {
+            artifacts.push(name.to_string());
+        }
+    }
 }
\ No newline at end of file
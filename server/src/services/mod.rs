@@ -58,6 +58,9 @@ pub trait ServerServices {
     fn generate_report(&self) -> ChaosResult<TestingReport>;
 
     fn set_metrics_for_agent(&self, agent : &str, metric_name : &str, metrics : MetricsArtifact) -> ChaosResult<()>;
+
+    /// Registers an artifact uploaded by an agent
+    fn add_artifact_for_agent(&self, agent : &str, artifact_name : &str) -> ChaosResult<()>;
     /// Gets a script to be used by the server
     fn get_sever_script(&self, script : &str) -> ChaosResult<String>;
 }
\ No newline at end of file
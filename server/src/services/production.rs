@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{repository::memory::MemoryRepository, utils::now_milliseconds};
+use crate::{domains::scenario::CalculatedScenario, repository::memory::MemoryRepository, utils::now_milliseconds};
 
 use super::ServerServices;
 use chaos_core::{
@@ -8,6 +8,7 @@ use chaos_core::{
     api::{agent::ConnectAgent, TestingReport},
     common::hash_params_and_actions,
     err::{ChaosError, ChaosResult},
+    parameters::TestParameters,
     scenario::TestScenario,
     tasks::{AgentTask, AgentTaskResult},
 };
@@ -83,7 +84,7 @@ impl ServerServices for ProductionService {
             )))
         };
         let scenario = db.scenarios.get(&scenario).ok_or(ChaosError::Unknown)?;
-        db.scenario = Some(scenario.into());
+        db.scenario = Some(scenario.try_into()?);
         Ok(())
     }
 
@@ -181,14 +182,17 @@ impl ServerServices for ProductionService {
             report: String::with_capacity(4096),
         };
         ret.add_h1(&scenario.name);
+        if let Some(seed) = scenario.scenario.chaos.seed {
+            ret.add_content(&format!("Chaos seed: {}", seed));
+        }
         ret.add_content("");
-        let mut id = 0;
         let mut last_scene: i32 = -1;
         let agents_total = db.state.len();
         let mut scene_ok = BTreeSet::new();
         for agent in db.state.keys() {
             scene_ok.insert(agent);
         }
+        let mut scene_results: BTreeMap<u32, usize> = BTreeMap::new();
         for task in &scenario.tasks {
             let task_type: &str = (&task.action).into();
             let task_id = task.id.to_string();
@@ -196,6 +200,7 @@ impl ServerServices for ProductionService {
                 if last_scene >= 0 {
                     ret.add_content("\n</details>\n");
                     ret.add_content(&format!("**Resume {}/{}**", scene_ok.len(), agents_total));
+                    scene_results.insert(last_scene as u32, scene_ok.len());
                 }
                 last_scene = task.scene_id as i32;
                 match scenario.scenes.get(&(last_scene as u32)) {
@@ -203,19 +208,20 @@ impl ServerServices for ProductionService {
                     None => ret.add_h2("Unknown scene"),
                 };
                 ret.add_content("\n<details>\n<summary>Show test</summary>\n");
-                ret.add_table_header(&["ID", "State", "Action", "Agent", "Hostname", "Error"]);
+                ret.add_table_header(&["ID", "State", "Action", "Agent", "Hostname", "Error", "Details"]);
                 scene_ok = BTreeSet::new();
                 for agent in db.state.keys() {
                     scene_ok.insert(agent);
                 }
             }
-            for (agent, state) in db.state.iter() {
+            for (agent, agent_state) in db.state.iter() {
                 let hostname = db
                     .agents
                     .get(agent.as_str())
                     .map(|v| v.hostname.clone())
                     .unwrap_or_default();
-                let (state, msg) = match state.results.get(&id).map(|v| v.result.clone()) {
+                let result = agent_state.results.get(&task.id);
+                let (state, msg) = match result.map(|v| v.result.clone()) {
                     Some(v) => match v {
                         Ok(_) => ("✅", String::new()),
                         Err(e) => {
@@ -228,6 +234,9 @@ impl ServerServices for ProductionService {
                         ("🕔", "Execution Pending".into())
                     }
                 };
+                let data = result.map(|v| task_data(&v.data)).unwrap_or_default();
+                let artifacts = artifact_links(agent, agent_state.artifacts.get(&task.id));
+                let details = [data, artifacts].into_iter().filter(|v| !v.is_empty()).collect::<Vec<String>>().join("<br>");
                 ret.add_table_row(&[
                     task_id.as_str(),
                     state,
@@ -235,12 +244,17 @@ impl ServerServices for ProductionService {
                     agent.as_str(),
                     &hostname,
                     msg.as_str(),
+                    details.as_str(),
                 ]);
-                id += 1;
             }
         }
         ret.add_content("\n</details>\n");
         ret.add_content(&format!("**Resume {}/{} {}**", scene_ok.len(), agents_total, if scene_ok.len() == agents_total {"✅"} else {"❌"}));
+        if last_scene >= 0 {
+            scene_results.insert(last_scene as u32, scene_ok.len());
+        }
+        add_matrix_summary(&mut ret, scenario, &scene_results, agents_total);
+        add_upgrade_grid(&mut ret, scenario, &scene_results, agents_total);
         Ok(ret)
     }
 
@@ -255,9 +269,89 @@ impl ServerServices for ProductionService {
         Ok(())
     }
 
+    fn add_artifact_for_agent(&self, agent : &str, artifact_name : &str) -> ChaosResult<()> {
+        let mut db = self.repo.db.lock().unwrap();
+        if db.scenario.is_none() {
+            return Ok(())
+        }
+        db.add_artifact(agent, artifact_name);
+        Ok(())
+    }
+
     fn get_sever_script(&self, script : &str) -> ChaosResult<String> {
         let script_path = std::env::current_dir().unwrap().join("workspace").join("scripts").join(script);
         let data = std::fs::read_to_string(&script_path)?;
         Ok(data)
     }
 }
+
+/// Data produced by the task. Ex: affected_pids=[1234]
+fn task_data(data : &TestParameters) -> String {
+    data.inner().iter().map(|(name, value)| format!("{}={}", name, serde_json::to_string(value).unwrap_or_default())).collect::<Vec<String>>().join("<br>")
+}
+
+/// Links to the artifacts stored in the server workspace
+fn artifact_links(agent : &str, artifacts : Option<&Vec<String>>) -> String {
+    let artifacts = match artifacts {
+        Some(v) => v,
+        None => return String::new()
+    };
+    artifacts.iter().map(|name| format!("[{}](workspace/{}/artifacts/{})", name, agent, name)).collect::<Vec<String>>().join("<br>")
+}
+
+/// Pivots the results of the matrix scenes by each matrix variable
+fn add_matrix_summary(report : &mut TestingReport, scenario : &CalculatedScenario, scene_results : &BTreeMap<u32, usize>, agents_total : usize) {
+    // variable -> value -> (passed, total)
+    let mut axes: BTreeMap<&str, BTreeMap<&str, (usize, usize)>> = BTreeMap::new();
+    for (scene_id, combination) in &scenario.matrix {
+        let passed = scene_results.get(scene_id).copied().unwrap_or_default();
+        for (name, value) in combination.inner() {
+            let value : &str = value.try_into().unwrap_or_default();
+            let entry = axes.entry(name.as_str()).or_default().entry(value).or_default();
+            entry.0 += passed;
+            entry.1 += agents_total;
+        }
+    }
+    if axes.is_empty() {
+        return
+    }
+    report.add_h2("Matrix summary");
+    for (name, values) in axes {
+        report.add_h3(name);
+        report.add_table_header(&["Value", "Passed", "Total", "State"]);
+        for (value, (passed, total)) in values {
+            report.add_table_row(&[
+                value,
+                &passed.to_string(),
+                &total.to_string(),
+                if passed == total {"✅"} else {"❌"}
+            ]);
+        }
+        report.add_content("");
+    }
+}
+
+/// Grid with the result of each upgrade path: rows are the installed version and columns the upgraded one
+fn add_upgrade_grid(report : &mut TestingReport, scenario : &CalculatedScenario, scene_results : &BTreeMap<u32, usize>, agents_total : usize) {
+    if scenario.upgrades.is_empty() {
+        return
+    }
+    let versions : Vec<&str> = scenario.scenario.upgrade_paths.versions.iter().map(|v| v.version.as_str()).collect();
+    let mut header = vec!["From \\ To"];
+    header.extend(versions.iter());
+    report.add_h2("Upgrade paths");
+    report.add_table_header(&header);
+    for from in &versions {
+        let mut row = vec![from.to_string()];
+        for to in &versions {
+            let cell = scenario.upgrades.iter().find(|(_, (f, t))| f == from && t == to).map(|(scene_id, _)| {
+                let passed = scene_results.get(scene_id).copied().unwrap_or_default();
+                format!("{} {}/{}", if passed == agents_total {"✅"} else {"❌"}, passed, agents_total)
+            });
+            row.push(cell.unwrap_or_default());
+        }
+        let row : Vec<&str> = row.iter().map(|v| v.as_str()).collect();
+        report.add_table_row(&row);
+    }
+    report.add_content("");
+}